@@ -0,0 +1,186 @@
+//! 调整参数的 CPU 实现
+//!
+//! 与 `image.wgsl` 中的片段着色器保持一致，用于导出全分辨率图像。
+//! 和 GPU 一样先把像素线性化，在线性值上计算后再编码回 8 位。
+//! 画笔蒙版按原图分辨率重新栅格化。
+
+use super::Image;
+use editor_studio_math::{Homography, ImageSize, MaskRaster};
+use editor_studio_params::{AdjustmentParams, ColorGrading, ColorWheel, Mask, MaskShape};
+
+const LUMA: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// 输出像素到原图归一化坐标的映射，用于计算蒙版权重
+#[derive(Clone, Copy, Debug)]
+pub struct MaskMapping {
+    /// 输出像素坐标 -> 原图归一化坐标 (0-1)
+    pub to_source_uv: Homography,
+    /// 原图尺寸 (决定宽高比和画笔蒙版的栅格化分辨率)
+    pub source: ImageSize,
+}
+
+/// 调整参数应用
+pub struct ImageAdjust;
+
+impl ImageAdjust {
+    /// 应用基础调整和色彩分级
+    pub fn apply(image: &Image, params: &AdjustmentParams, grading: &ColorGrading) -> Image {
+        let mapping = MaskMapping {
+            to_source_uv: Homography::IDENTITY,
+            source: image.size(),
+        };
+        Self::apply_with_masks(image, params, grading, &[], &mapping)
+    }
+
+    /// 读取归一化坐标 `(u, v)` 处像素的线性值 (用于颜色范围蒙版取样)
+    pub fn sample_linear(image: &Image, u: f32, v: f32) -> Option<[f32; 3]> {
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) || image.pixel_count() == 0 {
+            return None;
+        }
+        let x = ((u * image.width as f32) as u32).min(image.width - 1);
+        let y = ((v * image.height as f32) as u32).min(image.height - 1);
+        let offset = (y as usize * image.width as usize + x as usize) * 4;
+        let pixel = image.data.get(offset..offset + 3)?;
+        let decode = srgb_decode_table();
+        Some([0, 1, 2].map(|c| decode[pixel[c] as usize]))
+    }
+
+    /// 应用基础调整、色彩分级和局部调整蒙版
+    pub fn apply_with_masks(
+        image: &Image,
+        params: &AdjustmentParams,
+        grading: &ColorGrading,
+        masks: &[Mask],
+        mapping: &MaskMapping,
+    ) -> Image {
+        let mut result = image.clone();
+        if *params == AdjustmentParams::default() && grading.is_neutral() && masks.is_empty() {
+            return result;
+        }
+
+        let decode = srgb_decode_table();
+        let offsets = GradingOffsets::new(grading);
+        let width = image.width as usize;
+        let aspect = mapping.source.width as f32 / mapping.source.height as f32;
+        let rasters: Vec<Option<MaskRaster>> = masks
+            .iter()
+            .map(|mask| match &mask.shape {
+                MaskShape::Brush { strokes } => Some(MaskRaster::from_strokes(
+                    strokes,
+                    mapping.source.width,
+                    mapping.source.height,
+                )),
+                _ => None,
+            })
+            .collect();
+        for (i, pixel) in result.data.chunks_exact_mut(4).enumerate() {
+            let rgb = [0, 1, 2].map(|c| decode[pixel[c] as usize]);
+            let mut adjusted = offsets.apply(grading, adjust_basic(rgb, params));
+
+            if !masks.is_empty() {
+                let x = (i % width) as f32 + 0.5;
+                let y = (i / width) as f32 + 0.5;
+                let (u, v) = mapping.to_source_uv.apply(x, y);
+                for (mask, raster) in masks.iter().zip(&rasters) {
+                    let weight = match raster {
+                        Some(raster) => mask.weight_from_coverage(raster.sample(u, v), rgb),
+                        None => mask.weight(u, v, aspect, rgb).unwrap_or(0.0),
+                    };
+                    if weight > 0.0 {
+                        let local = adjust_basic(adjusted, &mask.adjustments);
+                        for (value, target) in adjusted.iter_mut().zip(local) {
+                            *value += (target - *value) * weight;
+                        }
+                    }
+                }
+            }
+
+            for (c, value) in adjusted.into_iter().enumerate() {
+                pixel[c] = srgb_encode(value);
+            }
+        }
+        result
+    }
+}
+
+fn adjust_basic(rgb: [f32; 3], params: &AdjustmentParams) -> [f32; 3] {
+    let contrasted = rgb.map(|v| (v + params.brightness - 0.5) * params.contrast + 0.5);
+    let gray = dot(contrasted, LUMA);
+    contrasted.map(|v| gray + (v - gray) * params.saturation)
+}
+
+/// 预先计算的各色轮偏移
+struct GradingOffsets {
+    shadows: [f32; 3],
+    midtones: [f32; 3],
+    highlights: [f32; 3],
+    global: [f32; 3],
+}
+
+impl GradingOffsets {
+    fn new(grading: &ColorGrading) -> Self {
+        Self {
+            shadows: wheel_offset(&grading.shadows),
+            midtones: wheel_offset(&grading.midtones),
+            highlights: wheel_offset(&grading.highlights),
+            global: wheel_offset(&grading.global),
+        }
+    }
+
+    fn apply(&self, grading: &ColorGrading, rgb: [f32; 3]) -> [f32; 3] {
+        let luma = dot(rgb, LUMA).clamp(0.0, 1.0);
+        let pivot = 0.5 - grading.balance * 0.4;
+        let falloff = (1.0 - 2.0 * grading.blending).exp2();
+        let shadows = (1.0 - smoothstep(0.0, pivot, luma)).powf(falloff);
+        let highlights = smoothstep(pivot, 1.0, luma).powf(falloff);
+        let midtones = 1.0 - shadows - highlights;
+
+        let mut out = rgb;
+        for (c, value) in out.iter_mut().enumerate() {
+            *value += self.shadows[c] * shadows
+                + self.midtones[c] * midtones
+                + self.highlights[c] * highlights
+                + self.global[c];
+        }
+        out
+    }
+}
+
+fn wheel_offset(wheel: &ColorWheel) -> [f32; 3] {
+    let h = wheel.hue / 360.0;
+    let hue_color = [1.0, 2.0 / 3.0, 1.0 / 3.0]
+        .map(|offset: f32| (((h + offset).fract() * 6.0 - 3.0).abs() - 1.0).clamp(0.0, 1.0));
+    let luma = dot(hue_color, LUMA);
+    hue_color.map(|v| (v - luma) * wheel.saturation * 0.5 + wheel.luminance * 0.5)
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// 8 位 sRGB 编码值到线性值的查找表
+pub(crate) fn srgb_decode_table() -> [f32; 256] {
+    std::array::from_fn(|i| {
+        let v = i as f32 / 255.0;
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+pub(crate) fn srgb_encode(linear: f32) -> u8 {
+    let v = linear.clamp(0.0, 1.0);
+    let encoded = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
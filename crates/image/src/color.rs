@@ -0,0 +1,188 @@
+//! 色彩管理模块
+//!
+//! 解码时按嵌入的 ICC 配置文件把像素转换到工作色彩空间，
+//! 导出时再转换到目标色彩空间并生成要嵌入文件的 ICC 配置文件。
+
+use super::Image;
+use editor_studio_params::ColorSpace;
+use qcms::{DataType, Intent, Profile, Transform};
+
+/// 色彩管理
+pub struct ColorManager;
+
+impl ColorManager {
+    /// 把 RGBA8 像素从嵌入的 ICC 配置文件转换到目标色彩空间
+    ///
+    /// 没有 ICC 配置文件、配置文件无法解析或不是 RGB 配置文件时按 sRGB 处理。
+    pub fn convert_from_icc(data: &mut [u8], icc_profile: Option<&[u8]>, target: ColorSpace) {
+        let source = icc_profile.and_then(|icc| Profile::new_from_slice(icc, false));
+        let output = Self::profile(target);
+
+        let transform = source.as_deref().and_then(|source| {
+            Transform::new(source, &output, DataType::RGBA8, Intent::Perceptual)
+        });
+
+        match transform {
+            Some(transform) => transform.apply(data),
+            None => Self::convert_pixels(data, ColorSpace::Srgb, target),
+        }
+    }
+
+    /// 把图像转换到目标色彩空间
+    pub fn convert(image: &Image, target: ColorSpace) -> Image {
+        let mut result = image.clone();
+        Self::convert_pixels(&mut result.data, image.color_space, target);
+        result.color_space = target;
+        result
+    }
+
+    /// 在两个标准色彩空间之间转换 RGBA8 像素
+    pub fn convert_pixels(data: &mut [u8], source: ColorSpace, target: ColorSpace) {
+        if source == target {
+            return;
+        }
+
+        let transform = Transform::new(
+            &Self::profile(source),
+            &Self::profile(target),
+            DataType::RGBA8,
+            Intent::Perceptual,
+        )
+        .expect("built-in profiles are always valid RGB profiles");
+        transform.apply(data);
+    }
+
+    /// 生成色彩空间对应的 ICC v4 配置文件 (矩阵/TRC 类型)
+    pub fn icc_profile(space: ColorSpace) -> Vec<u8> {
+        let description = match space {
+            ColorSpace::Srgb => "sRGB",
+            ColorSpace::DisplayP3 => "Display P3",
+        };
+
+        // ICC 的 PCS 为 D50，原色需要先做 Bradford 色适应
+        let to_xyz = space.to_xyz();
+        let mut colorants = [[0.0f32; 3]; 3];
+        for (channel, colorant) in colorants.iter_mut().enumerate() {
+            let xyz = [to_xyz[0][channel], to_xyz[1][channel], to_xyz[2][channel]];
+            *colorant =
+                BRADFORD_D65_TO_D50.map(|row| row[0] * xyz[0] + row[1] * xyz[1] + row[2] * xyz[2]);
+        }
+
+        let mut tags: Vec<([u8; 4], Vec<u8>)> = vec![
+            (*b"desc", mluc_tag(description)),
+            (*b"cprt", mluc_tag("No copyright, use freely")),
+            (*b"wtpt", xyz_tag(D50)),
+            (*b"chad", sf32_tag(&BRADFORD_D65_TO_D50.concat())),
+            (*b"rXYZ", xyz_tag(colorants[0])),
+            (*b"gXYZ", xyz_tag(colorants[1])),
+            (*b"bXYZ", xyz_tag(colorants[2])),
+        ];
+        let trc = srgb_trc_tag();
+        for signature in [*b"rTRC", *b"gTRC", *b"bTRC"] {
+            tags.push((signature, trc.clone()));
+        }
+
+        write_profile(&tags)
+    }
+
+    fn profile(space: ColorSpace) -> Box<Profile> {
+        match space {
+            ColorSpace::Srgb => Profile::new_sRGB(),
+            ColorSpace::DisplayP3 => Profile::new_from_slice(&Self::icc_profile(space), false)
+                .expect("generated profile is always parseable"),
+        }
+    }
+}
+
+/// D50 白点 (ICC PCS)
+const D50: [f32; 3] = [0.9642, 1.0, 0.8249];
+
+/// Bradford 色适应矩阵 D65 -> D50
+const BRADFORD_D65_TO_D50: [[f32; 3]; 3] = [
+    [1.047_811, 0.022_886_6, -0.050_127],
+    [0.029_542_4, 0.990_484_4, -0.017_049_1],
+    [-0.009_234_5, 0.015_043_6, 0.752_131_6],
+];
+
+/// 写入完整的 ICC 配置文件 (头部 + 标签表 + 标签数据)
+fn write_profile(tags: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    const HEADER_SIZE: usize = 128;
+    let table_size = 4 + tags.len() * 12;
+
+    let mut table = Vec::with_capacity(table_size);
+    let mut body = Vec::new();
+    table.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+    for (signature, data) in tags {
+        let offset = HEADER_SIZE + table_size + body.len();
+        table.extend_from_slice(signature);
+        table.extend_from_slice(&(offset as u32).to_be_bytes());
+        table.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(data);
+        // 标签数据按 4 字节对齐
+        body.resize(body.len().next_multiple_of(4), 0);
+    }
+
+    let total_size = HEADER_SIZE + table.len() + body.len();
+    let mut header = vec![0u8; HEADER_SIZE];
+    header[0..4].copy_from_slice(&(total_size as u32).to_be_bytes());
+    header[8..12].copy_from_slice(&0x0430_0000u32.to_be_bytes());
+    header[12..16].copy_from_slice(b"mntr");
+    header[16..20].copy_from_slice(b"RGB ");
+    header[20..24].copy_from_slice(b"XYZ ");
+    // 创建日期固定为 2024-01-01，保证输出稳定
+    for (i, value) in [2024u16, 1, 1, 0, 0, 0].iter().enumerate() {
+        header[24 + i * 2..26 + i * 2].copy_from_slice(&value.to_be_bytes());
+    }
+    header[36..40].copy_from_slice(b"acsp");
+    for (i, value) in D50.iter().enumerate() {
+        header[68 + i * 4..72 + i * 4].copy_from_slice(&s15_fixed16(*value));
+    }
+
+    let mut profile = header;
+    profile.extend_from_slice(&table);
+    profile.extend_from_slice(&body);
+    profile
+}
+
+fn s15_fixed16(value: f32) -> [u8; 4] {
+    ((value * 65536.0).round() as i32).to_be_bytes()
+}
+
+fn xyz_tag(xyz: [f32; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    for value in xyz {
+        tag.extend_from_slice(&s15_fixed16(value));
+    }
+    tag
+}
+
+fn sf32_tag(values: &[f32]) -> Vec<u8> {
+    let mut tag = b"sf32\0\0\0\0".to_vec();
+    for &value in values {
+        tag.extend_from_slice(&s15_fixed16(value));
+    }
+    tag
+}
+
+fn mluc_tag(text: &str) -> Vec<u8> {
+    let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    let mut tag = b"mluc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&1u32.to_be_bytes()); // 记录数
+    tag.extend_from_slice(&12u32.to_be_bytes()); // 记录大小
+    tag.extend_from_slice(b"enUS");
+    tag.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+    tag.extend_from_slice(&28u32.to_be_bytes()); // 字符串偏移
+    tag.extend_from_slice(&utf16);
+    tag
+}
+
+/// sRGB 传递函数，用参数曲线类型 3 表示
+fn srgb_trc_tag() -> Vec<u8> {
+    let mut tag = b"para\0\0\0\0".to_vec();
+    tag.extend_from_slice(&3u16.to_be_bytes());
+    tag.extend_from_slice(&[0, 0]);
+    for value in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045] {
+        tag.extend_from_slice(&s15_fixed16(value));
+    }
+    tag
+}
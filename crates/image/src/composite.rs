@@ -0,0 +1,77 @@
+//! 图层合成 (CPU，用于导出)
+//!
+//! 在线性工作色彩空间中，按图层顺序从下到上逐个混合。
+
+use super::adjust::{srgb_decode_table, srgb_encode};
+use super::transform::sample_bilinear;
+use super::Image;
+use editor_studio_math::{layer_to_uv, ImageSize};
+use editor_studio_params::Layer;
+use std::collections::HashMap;
+
+/// 按编号保存的图层图像，像素需与底图处于同一色彩空间
+pub type LayerImages = HashMap<u32, Image>;
+
+/// 图层合成
+pub struct LayerCompositor;
+
+impl LayerCompositor {
+    /// 把图层合成到输出图像 (或其中位于 `(x, y)` 处的区域) 上
+    ///
+    /// 找不到图像的图层和隐藏的图层被跳过。
+    ///
+    /// # 参数
+    /// * `output` - 完整输出图像的尺寸，图层位置相对于它计算
+    pub fn composite(
+        image: &Image,
+        layers: &[Layer],
+        images: &LayerImages,
+        output: &ImageSize,
+        x: u32,
+        y: u32,
+    ) -> Image {
+        let mut result = image.clone();
+        let placed: Vec<_> = layers
+            .iter()
+            .filter(|layer| layer.is_visible())
+            .filter_map(|layer| {
+                let layer_image = images.get(&layer.image)?;
+                let to_uv = layer_to_uv(&layer.transform, output, &layer_image.size());
+                Some((layer, layer_image, to_uv))
+            })
+            .collect();
+        if placed.is_empty() {
+            return result;
+        }
+
+        let decode = srgb_decode_table();
+        let width = image.width as usize;
+        for (i, pixel) in result.data.chunks_exact_mut(4).enumerate() {
+            let px = x as f32 + (i % width) as f32 + 0.5;
+            let py = y as f32 + (i / width) as f32 + 0.5;
+            let mut rgb = [0, 1, 2].map(|c| decode[pixel[c] as usize]);
+            let mut alpha = pixel[3] as f32 / 255.0;
+
+            for (layer, layer_image, to_uv) in &placed {
+                let (u, v) = to_uv.apply(px, py);
+                if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+                    continue;
+                }
+                let top = sample_bilinear(
+                    layer_image,
+                    u * layer_image.width as f32 - 0.5,
+                    v * layer_image.height as f32 - 0.5,
+                );
+                let top_alpha = top[3] as f32 / 255.0;
+                rgb = layer.composite(rgb, [0, 1, 2].map(|c| decode[top[c] as usize]), top_alpha);
+                alpha += top_alpha * layer.opacity * (1.0 - alpha);
+            }
+
+            for (c, value) in rgb.into_iter().enumerate() {
+                pixel[c] = srgb_encode(value);
+            }
+            pixel[3] = (alpha * 255.0).round() as u8;
+        }
+        result
+    }
+}
@@ -1,21 +1,40 @@
 //! 图像解码模块
 
-use super::Image;
+use super::{ColorManager, Image};
+use editor_studio_params::ColorSpace;
+use image::ImageDecoder as _;
+use std::io::Cursor;
 
 /// 图像解码器
 pub struct ImageDecoder;
 
 impl ImageDecoder {
-    /// 从字节数据解码图像
+    /// 从字节数据解码图像，并转换到工作色彩空间
     pub fn decode(data: &[u8]) -> Result<Image, String> {
+        Self::decode_to(data, ColorSpace::WORKING)
+    }
+
+    /// 从字节数据解码图像，并按嵌入的 ICC 配置文件转换到指定色彩空间
+    pub fn decode_to(data: &[u8], color_space: ColorSpace) -> Result<Image, String> {
         // 使用 image crate 解码
-        let dyn_image =
-            image::load_from_memory(data).map_err(|e| format!("Failed to decode image: {}", e))?;
+        let mut decoder = image::ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .and_then(|reader| reader.into_decoder().map_err(std::io::Error::other))
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+        // 读取嵌入的 ICC 配置文件 (读取失败时按 sRGB 处理)
+        let icc_profile = decoder.icc_profile().ok().flatten();
+
+        let dyn_image = image::DynamicImage::from_decoder(decoder)
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
 
         // 转换为 RGBA8
         let rgba_image = dyn_image.to_rgba8();
         let (width, height) = rgba_image.dimensions();
-        let data = rgba_image.into_raw();
+        let mut data = rgba_image.into_raw();
+
+        // 转换到目标色彩空间
+        ColorManager::convert_from_icc(&mut data, icc_profile.as_deref(), color_space);
 
         // 检测原始格式
         let format = Self::detect_format(&data);
@@ -25,6 +44,7 @@ impl ImageDecoder {
             width,
             height,
             format,
+            color_space,
         })
     }
 
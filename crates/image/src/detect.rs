@@ -0,0 +1,101 @@
+//! 主体检测
+//!
+//! [`SubjectDetector`] 在图像中找出带类别和置信度的边界框 (归一化坐标)，
+//! 结果可以转换为裁剪建议使用的 [`Subject`]。所有检测都在本地进行：
+//! 模型由应用以字节形式提供 (见 `onnx` feature 下的 `OnnxDetector`)，不需要任何网络访问。
+//! [`StubDetector`] 返回固定的结果，可以在没有模型时使用应用已有的检测结果。
+
+use super::Image;
+use editor_studio_math::Subject;
+use editor_studio_params::CropRect;
+
+/// 一个检测结果
+#[derive(Clone, Debug, PartialEq)]
+pub struct Detection {
+    /// 类别名称 (如 `"face"`、`"person"`)
+    pub label: String,
+    /// 置信度 (0.0 到 1.0)
+    pub score: f32,
+    /// 边界框 (归一化坐标)
+    pub rect: CropRect,
+}
+
+impl Detection {
+    pub fn new(label: impl Into<String>, score: f32, rect: CropRect) -> Self {
+        Self {
+            label: label.into(),
+            score,
+            rect,
+        }
+    }
+
+    /// 是否为人脸
+    pub fn is_face(&self) -> bool {
+        self.label.eq_ignore_ascii_case("face")
+    }
+
+    /// 转换为裁剪建议的主体，重要程度为置信度
+    pub fn to_subject(&self) -> Subject {
+        let subject = if self.is_face() {
+            Subject::face(self.rect)
+        } else {
+            Subject::new(self.rect)
+        };
+        subject.with_importance(self.score)
+    }
+}
+
+/// 主体检测器
+pub trait SubjectDetector {
+    /// 检测图像中的主体，按置信度从高到低排列
+    fn detect(&self, image: &Image) -> Result<Vec<Detection>, String>;
+}
+
+/// 返回固定结果的检测器 (没有模型时使用)
+#[derive(Clone, Debug, Default)]
+pub struct StubDetector {
+    pub detections: Vec<Detection>,
+}
+
+impl StubDetector {
+    pub fn new(detections: Vec<Detection>) -> Self {
+        Self { detections }
+    }
+}
+
+impl SubjectDetector for StubDetector {
+    fn detect(&self, _image: &Image) -> Result<Vec<Detection>, String> {
+        let mut detections = self.detections.clone();
+        detections.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(detections)
+    }
+}
+
+/// 非极大值抑制：按置信度从高到低保留，去掉与已保留的同类结果重叠 (交并比) 超过
+/// `max_overlap` 的结果
+pub fn non_max_suppression(mut detections: Vec<Detection>, max_overlap: f32) -> Vec<Detection> {
+    detections.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<Detection> = Vec::with_capacity(detections.len());
+    for detection in detections {
+        let suppressed = kept.iter().any(|other| {
+            other.label == detection.label && overlap(&other.rect, &detection.rect) > max_overlap
+        });
+        if !suppressed {
+            kept.push(detection);
+        }
+    }
+    kept
+}
+
+/// 交并比
+fn overlap(a: &CropRect, b: &CropRect) -> f32 {
+    let width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    let intersection = width.max(0.0) * height.max(0.0);
+    let union = a.width * a.height + b.width * b.height - intersection;
+    if union <= 0.0 {
+        0.0
+    } else {
+        intersection / union
+    }
+}
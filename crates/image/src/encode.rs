@@ -0,0 +1,69 @@
+//! 图像编码模块
+
+use super::{ColorManager, ExportConfig, Image, ImageFormat};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder as _};
+
+/// 图像编码器
+pub struct ImageEncoder;
+
+impl ImageEncoder {
+    /// 按导出配置编码图像
+    ///
+    /// 先叠加 `config.watermark`，再把像素转换到 `config.color_space`，
+    /// 对应的 ICC 配置文件随文件一起写入。WebP 只支持无损编码，`quality` 对其无效。
+    pub fn encode(image: &Image, config: &ExportConfig) -> Result<Vec<u8>, String> {
+        let image = match &config.watermark {
+            Some(watermark) => ColorManager::convert(&watermark.apply(image), config.color_space),
+            None => ColorManager::convert(image, config.color_space),
+        };
+        let icc_profile = ColorManager::icc_profile(config.color_space);
+
+        let mut output = Vec::new();
+        let result = match config.format {
+            ImageFormat::Jpeg => {
+                // JPEG 不支持透明通道
+                let rgb: Vec<u8> = image
+                    .data
+                    .chunks_exact(4)
+                    .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                    .collect();
+                let mut encoder = JpegEncoder::new_with_quality(&mut output, config.quality.max(1));
+                encoder
+                    .set_icc_profile(icc_profile)
+                    .map_err(|e| format!("Failed to embed ICC profile: {}", e))?;
+                encoder.write_image(&rgb, image.width, image.height, ExtendedColorType::Rgb8)
+            }
+            ImageFormat::Png => {
+                let mut encoder = PngEncoder::new(&mut output);
+                encoder
+                    .set_icc_profile(icc_profile)
+                    .map_err(|e| format!("Failed to embed ICC profile: {}", e))?;
+                encoder.write_image(
+                    &image.data,
+                    image.width,
+                    image.height,
+                    ExtendedColorType::Rgba8,
+                )
+            }
+            ImageFormat::WebP => {
+                let mut encoder = WebPEncoder::new_lossless(&mut output);
+                encoder
+                    .set_icc_profile(icc_profile)
+                    .map_err(|e| format!("Failed to embed ICC profile: {}", e))?;
+                encoder.write_image(
+                    &image.data,
+                    image.width,
+                    image.height,
+                    ExtendedColorType::Rgba8,
+                )
+            }
+            ImageFormat::Rgba8 => return Ok(image.data),
+        };
+
+        result.map_err(|e| format!("Failed to encode image: {}", e))?;
+        Ok(output)
+    }
+}
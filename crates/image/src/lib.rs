@@ -1,12 +1,37 @@
 //! 图像处理模块
 
+pub mod adjust;
+pub mod color;
+pub mod composite;
 pub mod decode;
+pub mod detect;
+pub mod encode;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod pipeline;
+pub mod proxy;
+pub mod saliency;
+pub mod straighten;
 pub mod transform;
+pub mod watermark;
 
+pub use adjust::{ImageAdjust, MaskMapping};
+pub use color::ColorManager;
+pub use composite::{LayerCompositor, LayerImages};
 pub use decode::{ImageDecoder, ImageFormat};
+pub use detect::{non_max_suppression, Detection, StubDetector, SubjectDetector};
+pub use encode::ImageEncoder;
+#[cfg(feature = "onnx")]
+pub use onnx::{OnnxDetector, OnnxDetectorConfig, OutputLayout, COCO_LABELS};
+pub use pipeline::EditPipeline;
+pub use proxy::{Proxy, DEFAULT_PROXY_DIMENSION};
+pub use saliency::{Saliency, SaliencyMap};
+pub use straighten::{AutoStraighten, DetectedLine, LineOrientation, StraightenSuggestion};
 pub use transform::{ImageTransform, ResizeOptions};
+pub use watermark::{Watermark, WatermarkAnchor, WatermarkContent};
 
 use editor_studio_math::ImageSize;
+use editor_studio_params::ColorSpace;
 
 /// 图像数据
 #[derive(Clone)]
@@ -15,6 +40,8 @@ pub struct Image {
     pub width: u32,
     pub height: u32,
     pub format: ImageFormat,
+    /// 像素数据所在的色彩空间
+    pub color_space: ColorSpace,
 }
 
 impl Image {
@@ -31,9 +58,16 @@ impl Image {
             width,
             height,
             format,
+            color_space: ColorSpace::default(),
         }
     }
 
+    /// 指定像素数据所在的色彩空间
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
     /// 获取尺寸
     pub fn size(&self) -> ImageSize {
         ImageSize::new(self.width, self.height)
@@ -54,6 +88,10 @@ impl Image {
 pub struct ExportConfig {
     pub format: ImageFormat,
     pub quality: u8, // 0-100
+    /// 输出色彩空间 (对应的 ICC 配置文件会嵌入文件)
+    pub color_space: ColorSpace,
+    /// 编码前最后叠加的水印
+    pub watermark: Option<Watermark>,
 }
 
 impl Default for ExportConfig {
@@ -61,6 +99,8 @@ impl Default for ExportConfig {
         Self {
             format: ImageFormat::Jpeg,
             quality: 90,
+            color_space: ColorSpace::Srgb,
+            watermark: None,
         }
     }
 }
@@ -70,6 +110,8 @@ impl ExportConfig {
         Self {
             format: ImageFormat::Jpeg,
             quality: quality.clamp(0, 100),
+            color_space: ColorSpace::Srgb,
+            watermark: None,
         }
     }
 
@@ -77,6 +119,8 @@ impl ExportConfig {
         Self {
             format: ImageFormat::Png,
             quality: 100,
+            color_space: ColorSpace::Srgb,
+            watermark: None,
         }
     }
 
@@ -84,6 +128,20 @@ impl ExportConfig {
         Self {
             format: ImageFormat::WebP,
             quality: quality.clamp(0, 100),
+            color_space: ColorSpace::Srgb,
+            watermark: None,
         }
     }
+
+    /// 指定输出色彩空间
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// 导出时叠加水印
+    pub fn with_watermark(mut self, watermark: Option<Watermark>) -> Self {
+        self.watermark = watermark;
+        self
+    }
 }
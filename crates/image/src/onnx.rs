@@ -0,0 +1,334 @@
+//! ONNX 主体检测 (tract 推理)
+//!
+//! 纯 Rust 的本地推理后端：模型由应用以字节形式提供，加载和推理都不访问网络。
+//! 支持两种常见的输出格式：YOLO (v8 及以后，单个输出 `[1, 4 + 类别数, N]`)
+//! 和分开的分数与边界框 (如 UltraFace、SSD)。输入图像直接拉伸到模型的输入尺寸，
+//! 检测结果换算回原图的归一化坐标。
+
+use super::detect::{non_max_suppression, Detection, SubjectDetector};
+use super::{Image, ImageTransform, ResizeOptions};
+use editor_studio_params::CropRect;
+use tract_onnx::prelude::*;
+
+/// COCO 数据集的 80 个类别 (官方 YOLO 模型的输出顺序)
+pub const COCO_LABELS: [&str; 80] = [
+    "person",
+    "bicycle",
+    "car",
+    "motorcycle",
+    "airplane",
+    "bus",
+    "train",
+    "truck",
+    "boat",
+    "traffic light",
+    "fire hydrant",
+    "stop sign",
+    "parking meter",
+    "bench",
+    "bird",
+    "cat",
+    "dog",
+    "horse",
+    "sheep",
+    "cow",
+    "elephant",
+    "bear",
+    "zebra",
+    "giraffe",
+    "backpack",
+    "umbrella",
+    "handbag",
+    "tie",
+    "suitcase",
+    "frisbee",
+    "skis",
+    "snowboard",
+    "sports ball",
+    "kite",
+    "baseball bat",
+    "baseball glove",
+    "skateboard",
+    "surfboard",
+    "tennis racket",
+    "bottle",
+    "wine glass",
+    "cup",
+    "fork",
+    "knife",
+    "spoon",
+    "bowl",
+    "banana",
+    "apple",
+    "sandwich",
+    "orange",
+    "broccoli",
+    "carrot",
+    "hot dog",
+    "pizza",
+    "donut",
+    "cake",
+    "chair",
+    "couch",
+    "potted plant",
+    "bed",
+    "dining table",
+    "toilet",
+    "tv",
+    "laptop",
+    "mouse",
+    "remote",
+    "keyboard",
+    "cell phone",
+    "microwave",
+    "oven",
+    "toaster",
+    "sink",
+    "refrigerator",
+    "book",
+    "clock",
+    "vase",
+    "scissors",
+    "teddy bear",
+    "hair drier",
+    "toothbrush",
+];
+
+/// 模型的输出格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// 单个输出 `[1, 4 + 类别数, N]` (或 `[1, N, 4 + 类别数]`)，
+    /// 每个框为输入像素坐标的 `cx, cy, w, h` 加各类别的分数
+    #[default]
+    Yolo,
+    /// 两个输出：分数 `[1, N, 类别数]` 和归一化坐标的边界框 `[1, N, 4]` (`x1, y1, x2, y2`)，
+    /// 第 0 类为背景
+    BoxesScores,
+}
+
+impl OutputLayout {
+    /// 从名称解析 (`"yolo"`、`"boxes-scores"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "yolo" => Some(Self::Yolo),
+            "boxes-scores" => Some(Self::BoxesScores),
+            _ => None,
+        }
+    }
+}
+
+/// ONNX 检测模型的配置
+#[derive(Clone, Debug, PartialEq)]
+pub struct OnnxDetectorConfig {
+    /// 模型输入宽度 (像素)
+    pub input_width: u32,
+    /// 模型输入高度 (像素)
+    pub input_height: u32,
+    pub layout: OutputLayout,
+    /// 类别名称，顺序与模型输出一致 (人脸类别命名为 `"face"`)
+    pub labels: Vec<String>,
+    /// 输入归一化：`(rgb / 255 - mean) / std`
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+    /// 置信度低于此值的结果被丢弃
+    pub score_threshold: f32,
+    /// 非极大值抑制的交并比阈值
+    pub max_overlap: f32,
+}
+
+impl OnnxDetectorConfig {
+    /// YOLO 模型 (640x640，输入为 0-1 的 RGB)，在 COCO 上训练的模型可以使用 [`COCO_LABELS`]
+    pub fn yolo(labels: Vec<String>) -> Self {
+        Self {
+            input_width: 640,
+            input_height: 640,
+            layout: OutputLayout::Yolo,
+            labels,
+            mean: [0.0; 3],
+            std: [1.0; 3],
+            score_threshold: 0.25,
+            max_overlap: 0.45,
+        }
+    }
+
+    /// UltraFace 人脸检测模型 (320x240)
+    pub fn ultraface() -> Self {
+        Self {
+            input_width: 320,
+            input_height: 240,
+            layout: OutputLayout::BoxesScores,
+            labels: vec!["background".to_string(), "face".to_string()],
+            mean: [127.0 / 255.0; 3],
+            std: [128.0 / 255.0; 3],
+            score_threshold: 0.7,
+            max_overlap: 0.3,
+        }
+    }
+}
+
+/// 基于 ONNX 模型的主体检测器
+pub struct OnnxDetector {
+    model: TypedRunnableModel<TypedModel>,
+    config: OnnxDetectorConfig,
+}
+
+impl OnnxDetector {
+    /// 从 ONNX 模型字节加载
+    pub fn from_bytes(model: &[u8], config: OnnxDetectorConfig) -> Result<Self, String> {
+        if config.labels.is_empty() {
+            return Err("Detector needs at least one label".to_string());
+        }
+        if config.input_width == 0 || config.input_height == 0 {
+            return Err("Invalid detector input size".to_string());
+        }
+        let shape = [
+            1,
+            3,
+            config.input_height as usize,
+            config.input_width as usize,
+        ];
+        let model = tract_onnx::onnx()
+            .model_for_read(&mut std::io::Cursor::new(model))
+            .and_then(|model| model.with_input_fact(0, f32::fact(shape).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| format!("Failed to load ONNX model: {}", e))?;
+        Ok(Self { model, config })
+    }
+
+    pub fn config(&self) -> &OnnxDetectorConfig {
+        &self.config
+    }
+
+    /// 缩放到模型输入尺寸并归一化为 NCHW 张量
+    fn input_tensor(&self, image: &Image) -> Tensor {
+        let config = &self.config;
+        let resized = ImageTransform::resize(
+            image,
+            ResizeOptions::ExactSize {
+                width: config.input_width,
+                height: config.input_height,
+            },
+        );
+        let (width, height) = (config.input_width as usize, config.input_height as usize);
+        tract_ndarray::Array4::from_shape_fn((1, 3, height, width), |(_, c, y, x)| {
+            let value = resized.data[(y * width + x) * 4 + c] as f32 / 255.0;
+            (value - config.mean[c]) / config.std[c]
+        })
+        .into_tensor()
+    }
+
+    /// 解码 YOLO 输出
+    fn decode_yolo(&self, outputs: &[TValue]) -> Result<Vec<Detection>, String> {
+        let output = output_view(outputs, 0)?;
+        let classes = self.config.labels.len();
+        let shape = output.shape();
+        if shape.len() != 3 || (shape[1] != 4 + classes && shape[2] != 4 + classes) {
+            return Err(format!("Unexpected YOLO output shape {:?}", shape));
+        }
+        let channels_first = shape[1] == 4 + classes;
+        let count = if channels_first { shape[2] } else { shape[1] };
+        let value = |i: usize, channel: usize| {
+            if channels_first {
+                output[[0, channel, i]]
+            } else {
+                output[[0, i, channel]]
+            }
+        };
+
+        let (width, height) = (
+            self.config.input_width as f32,
+            self.config.input_height as f32,
+        );
+        let mut detections = Vec::new();
+        for i in 0..count {
+            let Some((class, score)) = (0..classes)
+                .map(|class| (class, value(i, 4 + class)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                continue;
+            };
+            if score < self.config.score_threshold {
+                continue;
+            }
+            let (cx, cy) = (value(i, 0) / width, value(i, 1) / height);
+            let (w, h) = (value(i, 2) / width, value(i, 3) / height);
+            if let Some(rect) = clip_box(cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0) {
+                detections.push(Detection::new(&self.config.labels[class], score, rect));
+            }
+        }
+        Ok(detections)
+    }
+
+    /// 解码分数 + 边界框输出 (跳过第 0 类背景)
+    fn decode_boxes_scores(&self, outputs: &[TValue]) -> Result<Vec<Detection>, String> {
+        let scores = output_view(outputs, 0)?;
+        let boxes = output_view(outputs, 1)?;
+        let classes = self.config.labels.len();
+        let (score_shape, box_shape) = (scores.shape(), boxes.shape());
+        if score_shape.len() != 3
+            || box_shape.len() != 3
+            || score_shape[1] != box_shape[1]
+            || score_shape[2] != classes
+            || box_shape[2] != 4
+        {
+            return Err(format!(
+                "Unexpected detector output shapes {:?} and {:?}",
+                score_shape, box_shape
+            ));
+        }
+
+        let mut detections = Vec::new();
+        for i in 0..score_shape[1] {
+            let Some((class, score)) = (1..classes)
+                .map(|class| (class, scores[[0, i, class]]))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                continue;
+            };
+            if score < self.config.score_threshold {
+                continue;
+            }
+            let corners = [0, 1, 2, 3].map(|k| boxes[[0, i, k]]);
+            if let Some(rect) = clip_box(corners[0], corners[1], corners[2], corners[3]) {
+                detections.push(Detection::new(&self.config.labels[class], score, rect));
+            }
+        }
+        Ok(detections)
+    }
+}
+
+impl SubjectDetector for OnnxDetector {
+    fn detect(&self, image: &Image) -> Result<Vec<Detection>, String> {
+        if image.width == 0 || image.height == 0 {
+            return Ok(Vec::new());
+        }
+        let outputs = self
+            .model
+            .run(tvec!(self.input_tensor(image).into()))
+            .map_err(|e| format!("ONNX inference failed: {}", e))?;
+        let detections = match self.config.layout {
+            OutputLayout::Yolo => self.decode_yolo(&outputs)?,
+            OutputLayout::BoxesScores => self.decode_boxes_scores(&outputs)?,
+        };
+        Ok(non_max_suppression(detections, self.config.max_overlap))
+    }
+}
+
+fn output_view(
+    outputs: &[TValue],
+    index: usize,
+) -> Result<tract_ndarray::ArrayViewD<'_, f32>, String> {
+    outputs
+        .get(index)
+        .ok_or_else(|| format!("Detector output {} is missing", index))?
+        .to_array_view::<f32>()
+        .map_err(|e| format!("Invalid detector output {}: {}", index, e))
+}
+
+/// 把角点坐标限制在图像内，面积为 0 时返回 None
+fn clip_box(x1: f32, y1: f32, x2: f32, y2: f32) -> Option<CropRect> {
+    let (left, top) = (x1.clamp(0.0, 1.0), y1.clamp(0.0, 1.0));
+    let (right, bottom) = (x2.clamp(0.0, 1.0), y2.clamp(0.0, 1.0));
+    (right > left && bottom > top).then(|| CropRect::new(left, top, right - left, bottom - top))
+}
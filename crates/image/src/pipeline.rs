@@ -0,0 +1,86 @@
+//! 编辑渲染管线
+//!
+//! 每次都从未修改的原图开始，先应用几何操作栈，再应用调整参数，最后合成图层。
+
+use super::{Image, ImageAdjust, ImageTransform, LayerCompositor, LayerImages, MaskMapping};
+use editor_studio_math::{Affine2, GeometryPlan, Homography};
+use editor_studio_params::{CropRect, EditState};
+
+/// 编辑渲染管线
+pub struct EditPipeline;
+
+impl EditPipeline {
+    /// 按编辑状态渲染原图 (不合成图层)
+    pub fn render(source: &Image, state: &EditState) -> Image {
+        Self::render_layered(source, state, &LayerImages::new())
+    }
+
+    /// 按编辑状态渲染原图并合成图层
+    ///
+    /// # 参数
+    /// * `layers` - 图层引用的图像
+    pub fn render_layered(source: &Image, state: &EditState, layers: &LayerImages) -> Image {
+        let plan = GeometryPlan::new(&source.size(), &state.operations);
+        let geometry = ImageTransform::apply_operations(source, &state.operations);
+        let adjusted = Self::adjust(source, state, &geometry, &plan, 0, 0);
+        LayerCompositor::composite(&adjusted, &state.layers, layers, &plan.output, 0, 0)
+    }
+
+    /// 只渲染输出图像中的一个区域 (如 1:1 查看，不合成图层)
+    ///
+    /// # 参数
+    /// * `region` - 区域在输出图像中的归一化坐标 (0-1)
+    pub fn render_region(source: &Image, state: &EditState, region: &CropRect) -> Image {
+        Self::render_region_layered(source, state, &LayerImages::new(), region)
+    }
+
+    /// 只渲染输出图像中的一个区域并合成图层
+    ///
+    /// # 参数
+    /// * `layers` - 图层引用的图像
+    /// * `region` - 区域在输出图像中的归一化坐标 (0-1)
+    pub fn render_region_layered(
+        source: &Image,
+        state: &EditState,
+        layers: &LayerImages,
+        region: &CropRect,
+    ) -> Image {
+        let plan = GeometryPlan::new(&source.size(), &state.operations);
+        let (out_width, out_height) = (plan.output.width as f32, plan.output.height as f32);
+        let x = ((region.x * out_width).round() as u32).min(plan.output.width - 1);
+        let y = ((region.y * out_height).round() as u32).min(plan.output.height - 1);
+        let width = ((region.width * out_width).round() as u32).clamp(1, plan.output.width - x);
+        let height = ((region.height * out_height).round() as u32).clamp(1, plan.output.height - y);
+
+        let geometry = ImageTransform::resample_region(source, &plan, x, y, width, height);
+        let adjusted = Self::adjust(source, state, &geometry, &plan, x, y);
+        LayerCompositor::composite(&adjusted, &state.layers, layers, &plan.output, x, y)
+    }
+
+    /// 对几何变换后的图像 (位于输出图像的 `(x, y)` 处) 应用调整参数和蒙版
+    fn adjust(
+        source: &Image,
+        state: &EditState,
+        geometry: &Image,
+        plan: &GeometryPlan,
+        x: u32,
+        y: u32,
+    ) -> Image {
+        let mapping = MaskMapping {
+            to_source_uv: Homography::from(Affine2::scale(
+                1.0 / source.width as f32,
+                1.0 / source.height as f32,
+            ))
+            .then_from(&plan.to_source)
+            .then_from(&Affine2::translate(x as f32, y as f32).into()),
+            source: source.size(),
+        };
+        ImageAdjust::apply_with_masks(
+            geometry,
+            &state.adjustments,
+            &state.grading,
+            &state.masks,
+            &mapping,
+        )
+    }
+}
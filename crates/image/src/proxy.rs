@@ -0,0 +1,80 @@
+//! 预览代理
+//!
+//! 交互编辑时使用按显示尺寸缩小的代理图像，导出或 1:1 查看时再对原图
+//! 执行同一个编辑栈。编辑参数都是归一化坐标或角度，与分辨率无关。
+
+use super::Image;
+
+/// 默认的代理图像最长边 (像素)
+pub const DEFAULT_PROXY_DIMENSION: u32 = 2048;
+
+/// 预览代理图像
+#[derive(Clone)]
+pub struct Proxy {
+    /// 代理图像
+    pub image: Image,
+    /// 代理相对原图的缩放比例 (不超过 1.0)
+    pub scale: f32,
+}
+
+impl Proxy {
+    /// 生成最长边不超过 `max_dimension` 的代理，原图足够小时直接使用原图
+    pub fn new(source: &Image, max_dimension: u32) -> Self {
+        let longest = source.width.max(source.height);
+        if longest <= max_dimension.max(1) {
+            return Self {
+                image: source.clone(),
+                scale: 1.0,
+            };
+        }
+
+        let scale = max_dimension.max(1) as f32 / longest as f32;
+        let width = ((source.width as f32 * scale).round() as u32).max(1);
+        let height = ((source.height as f32 * scale).round() as u32).max(1);
+        Self {
+            image: downscale(source, width, height),
+            scale,
+        }
+    }
+
+    /// 代理是否就是原图分辨率
+    pub fn is_full_resolution(&self) -> bool {
+        self.scale >= 1.0
+    }
+}
+
+/// 按面积平均缩小图像 (盒式滤波)，避免最近邻缩小产生的摩尔纹
+fn downscale(source: &Image, width: u32, height: u32) -> Image {
+    let mut result = Image::new(width, height, source.format).with_color_space(source.color_space);
+    let x_ratio = source.width as f32 / width as f32;
+    let y_ratio = source.height as f32 / height as f32;
+
+    for y in 0..height {
+        let y0 = (y as f32 * y_ratio) as u32;
+        let y1 = (((y + 1) as f32 * y_ratio).ceil() as u32).clamp(y0 + 1, source.height);
+        for x in 0..width {
+            let x0 = (x as f32 * x_ratio) as u32;
+            let x1 = (((x + 1) as f32 * x_ratio).ceil() as u32).clamp(x0 + 1, source.width);
+
+            let mut sum = [0u32; 4];
+            for sy in y0..y1 {
+                let row = (sy * source.width) as usize * 4;
+                for pixel in
+                    source.data[row + x0 as usize * 4..row + x1 as usize * 4].chunks_exact(4)
+                {
+                    for (total, &value) in sum.iter_mut().zip(pixel) {
+                        *total += value as u32;
+                    }
+                }
+            }
+
+            let count = (x1 - x0) * (y1 - y0);
+            let dst_idx = (y * width + x) as usize * 4;
+            for (c, total) in sum.iter().enumerate() {
+                result.data[dst_idx + c] = ((total + count / 2) / count) as u8;
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,245 @@
+//! 显著性检测
+//!
+//! 不依赖机器学习模型的主体估计 (频率调谐法)：在缩小的图像上把颜色转换到 CIELAB，
+//! 轻微模糊去掉纹理和噪点后，计算每个像素与整幅图像平均颜色的距离，
+//! 与画面整体差别越大的区域越显著。阈值化后得到主体边界框和显著性重心，
+//! 可以直接作为 `editor-studio-math` 中裁剪函数的输入。
+
+use super::adjust::srgb_decode_table;
+use super::{Image, Proxy};
+use editor_studio_math::Subject;
+use editor_studio_params::CropRect;
+
+/// 分析用图像的最长边
+const ANALYSIS_DIMENSION: u32 = 256;
+/// 主体阈值为平均显著性的倍数
+const THRESHOLD_FACTOR: f32 = 2.0;
+/// 主体阈值的上限 (显著性整体偏高的画面)
+const MAX_THRESHOLD: f32 = 0.75;
+/// 阈值化之前对显著性图的模糊次数，把同一主体的碎片连成一片
+const REGION_BLUR_PASSES: usize = 2;
+/// 主体区域的最小面积 (相对于图像面积)
+const MIN_REGION_AREA: f32 = 0.005;
+/// 最多返回的主体数
+const MAX_REGIONS: usize = 5;
+
+/// sRGB (D65) 线性值到 XYZ 的矩阵
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+/// D65 白点
+const WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// 显著性图
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaliencyMap {
+    pub width: u32,
+    pub height: u32,
+    /// 逐像素显著性 (0.0 到 1.0，按行排列)
+    pub values: Vec<f32>,
+}
+
+/// 显著性检测
+pub struct Saliency;
+
+impl Saliency {
+    /// 计算显著性图 (分辨率为缩小后的分析图像)，颜色完全一致的图像返回 None
+    pub fn analyze(image: &Image) -> Option<SaliencyMap> {
+        let proxy = Proxy::new(image, ANALYSIS_DIMENSION);
+        let image = &proxy.image;
+        let (width, height) = (image.width as usize, image.height as usize);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let decode = srgb_decode_table();
+        let lab: Vec<[f32; 3]> = image
+            .data
+            .chunks_exact(4)
+            .map(|p| {
+                to_lab([
+                    decode[p[0] as usize],
+                    decode[p[1] as usize],
+                    decode[p[2] as usize],
+                ])
+            })
+            .collect();
+        let mean = lab.iter().fold([0.0f32; 3], |sum, c| {
+            [sum[0] + c[0], sum[1] + c[1], sum[2] + c[2]]
+        });
+        let mean = mean.map(|v| v / lab.len() as f32);
+
+        let channels: [Vec<f32>; 3] = std::array::from_fn(|channel| {
+            let values: Vec<f32> = lab.iter().map(|c| c[channel]).collect();
+            blur(&values, width, height)
+        });
+        let mut values: Vec<f32> = (0..width * height)
+            .map(|i| {
+                let d: [f32; 3] = std::array::from_fn(|c| channels[c][i] - mean[c]);
+                (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+            })
+            .collect();
+
+        let max = values.iter().copied().fold(0.0, f32::max);
+        if max <= f32::EPSILON {
+            return None;
+        }
+        for value in &mut values {
+            *value /= max;
+        }
+        Some(SaliencyMap {
+            width: image.width,
+            height: image.height,
+            values,
+        })
+    }
+}
+
+impl SaliencyMap {
+    /// 像素 `(x, y)` 的显著性
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        self.values[(y * self.width + x) as usize]
+    }
+
+    /// 主体阈值：平均显著性的两倍，不超过 0.75
+    pub fn threshold(&self) -> f32 {
+        let mean = self.values.iter().sum::<f32>() / self.values.len().max(1) as f32;
+        (THRESHOLD_FACTOR * mean).min(MAX_THRESHOLD)
+    }
+
+    /// 显著性重心 (归一化坐标)，只计高于平均显著性的部分，避免背景把重心拉向画面中央
+    pub fn center_of_mass(&self) -> Option<(f32, f32)> {
+        let mean = self.values.iter().sum::<f32>() / self.values.len().max(1) as f32;
+        let (mut sum_x, mut sum_y, mut total) = (0.0, 0.0, 0.0);
+        for (i, value) in self.values.iter().enumerate() {
+            let weight = (value - mean).max(0.0);
+            let x = (i as u32 % self.width) as f32 + 0.5;
+            let y = (i as u32 / self.width) as f32 + 0.5;
+            sum_x += weight * x;
+            sum_y += weight * y;
+            total += weight;
+        }
+        (total > 0.0).then(|| {
+            (
+                sum_x / total / self.width as f32,
+                sum_y / total / self.height as f32,
+            )
+        })
+    }
+
+    /// 主体边界框 (归一化坐标)，按显著性总量从高到低排列
+    pub fn subject_boxes(&self) -> Vec<CropRect> {
+        self.regions().into_iter().map(|(rect, _)| rect).collect()
+    }
+
+    /// 主体，重要程度为显著性总量相对于最显著主体的比例
+    pub fn subjects(&self) -> Vec<Subject> {
+        let regions = self.regions();
+        let most = regions.first().map_or(0.0, |(_, mass)| *mass);
+        regions
+            .into_iter()
+            .map(|(rect, mass)| Subject::new(rect).with_importance(mass / most))
+            .collect()
+    }
+
+    /// 阈值化后的连通区域 (8 邻域)：边界框和显著性总量
+    fn regions(&self) -> Vec<(CropRect, f32)> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut smoothed = self.values.clone();
+        for _ in 0..REGION_BLUR_PASSES {
+            smoothed = blur(&smoothed, width, height);
+        }
+        let threshold = self.threshold();
+        let min_pixels = (MIN_REGION_AREA * (width * height) as f32).ceil() as usize;
+
+        let mut visited = vec![false; width * height];
+        let mut regions = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..width * height {
+            if visited[start] || smoothed[start] < threshold {
+                continue;
+            }
+            visited[start] = true;
+            stack.push(start);
+            let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+            let (mut pixels, mut mass) = (0, 0.0);
+            while let Some(index) = stack.pop() {
+                let (x, y) = (index % width, index / width);
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x);
+                bottom = bottom.max(y);
+                pixels += 1;
+                mass += self.values[index];
+                for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                        let neighbor = ny * width + nx;
+                        if !visited[neighbor] && smoothed[neighbor] >= threshold {
+                            visited[neighbor] = true;
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+            }
+            if pixels >= min_pixels {
+                let rect = CropRect::new(
+                    left as f32 / width as f32,
+                    top as f32 / height as f32,
+                    (right + 1 - left) as f32 / width as f32,
+                    (bottom + 1 - top) as f32 / height as f32,
+                );
+                regions.push((rect, mass));
+            }
+        }
+        regions.sort_by(|a, b| b.1.total_cmp(&a.1));
+        regions.truncate(MAX_REGIONS);
+        regions
+    }
+}
+
+/// 线性 sRGB 到 CIELAB
+fn to_lab(rgb: [f32; 3]) -> [f32; 3] {
+    let xyz: [f32; 3] = std::array::from_fn(|i| {
+        let row = SRGB_TO_XYZ[i];
+        (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]) / WHITE[i]
+    });
+    let f = |t: f32| {
+        if t > 0.008_856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(xyz[0]), f(xyz[1]), f(xyz[2]));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// 5 阶二项式模糊 (可分离，边缘像素重复)
+fn blur(values: &[f32], width: usize, height: usize) -> Vec<f32> {
+    const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+    let pass = |source: &[f32], horizontal: bool| -> Vec<f32> {
+        (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                KERNEL
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| {
+                        let offset = k as isize - 2;
+                        let index = if horizontal {
+                            let nx = (x as isize + offset).clamp(0, width as isize - 1) as usize;
+                            y * width + nx
+                        } else {
+                            let ny = (y as isize + offset).clamp(0, height as isize - 1) as usize;
+                            ny * width + x
+                        };
+                        weight * source[index]
+                    })
+                    .sum()
+            })
+            .collect()
+    };
+    pass(&pass(values, true), false)
+}
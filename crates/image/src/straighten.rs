@@ -0,0 +1,264 @@
+//! 自动拉直
+//!
+//! 在缩小的灰度图上做边缘检测，再用霍夫变换找出接近水平 (地平线) 和接近竖直
+//! (建筑、门框) 的主要直线，由这些直线的倾斜角度估计使画面水平所需的旋转角度。
+//! 分析完全在 CPU 上进行，结果可以直接用于旋转或拉直工具。
+
+use super::{Image, Proxy};
+
+/// 分析用图像的最长边
+const ANALYSIS_DIMENSION: u32 = 512;
+/// 检测的最大倾斜角度 (度)
+const MAX_TILT: f32 = 20.0;
+/// 霍夫空间的角度分辨率 (度)
+const THETA_STEP: f32 = 0.1;
+/// 边缘强度阈值，相对于最强的边缘
+const EDGE_THRESHOLD: f32 = 0.2;
+/// 每组 (水平或竖直) 最多取的直线数
+const MAX_LINES: usize = 8;
+/// 直线的最少投票数，相对于图像短边
+const MIN_LINE_VOTES: f32 = 0.15;
+/// 直线角度在此范围内 (度) 视为一致
+const AGREEMENT: f32 = 0.5;
+
+const LUMA: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// 直线方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineOrientation {
+    /// 接近水平 (如地平线)
+    Horizontal,
+    /// 接近竖直 (如建筑边缘)
+    Vertical,
+}
+
+/// 检测到的直线
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetectedLine {
+    pub orientation: LineOrientation,
+    /// 相对于水平或竖直方向的倾斜角度 (度，顺时针)
+    pub tilt: f32,
+    /// 霍夫投票数 (直线上的边缘像素数)
+    pub votes: u32,
+}
+
+/// 自动拉直建议
+#[derive(Clone, Debug, PartialEq)]
+pub struct StraightenSuggestion {
+    /// 建议的旋转角度 (度，顺时针)，可直接用于旋转或拉直工具
+    pub angle: f32,
+    /// 置信度 (0.0 到 1.0)，综合直线的一致程度和长度
+    pub confidence: f32,
+    /// 与建议角度一致的直线
+    pub lines: Vec<DetectedLine>,
+}
+
+/// 自动拉直分析
+pub struct AutoStraighten;
+
+impl AutoStraighten {
+    /// 分析图像，没有找到足够明显的水平线或竖直线时返回 None
+    pub fn analyze(image: &Image) -> Option<StraightenSuggestion> {
+        let proxy = Proxy::new(image, ANALYSIS_DIMENSION);
+        let image = &proxy.image;
+        if image.width < 3 || image.height < 3 {
+            return None;
+        }
+
+        let edges = detect_edges(image);
+        let min_votes = (MIN_LINE_VOTES * image.width.min(image.height) as f32).max(1.0) as u32;
+        let mut lines = hough_lines(
+            &edges,
+            image.width,
+            image.height,
+            LineOrientation::Horizontal,
+        );
+        lines.extend(hough_lines(
+            &edges,
+            image.width,
+            image.height,
+            LineOrientation::Vertical,
+        ));
+        lines.retain(|line| line.votes >= min_votes);
+        if lines.is_empty() {
+            return None;
+        }
+
+        // 取支持票数最多的角度，再对一致的直线加权平均
+        let support = |tilt: f32| -> u32 {
+            lines
+                .iter()
+                .filter(|line| (line.tilt - tilt).abs() <= AGREEMENT)
+                .map(|line| line.votes)
+                .sum()
+        };
+        let best = lines
+            .iter()
+            .map(|line| line.tilt)
+            .max_by_key(|&tilt| support(tilt))?;
+        let agreeing: Vec<DetectedLine> = lines
+            .iter()
+            .filter(|line| (line.tilt - best).abs() <= AGREEMENT)
+            .copied()
+            .collect();
+        let agreeing_votes: u32 = agreeing.iter().map(|line| line.votes).sum();
+        let total_votes: u32 = lines.iter().map(|line| line.votes).sum();
+        let tilt = agreeing
+            .iter()
+            .map(|line| line.tilt * line.votes as f32)
+            .sum::<f32>()
+            / agreeing_votes as f32;
+
+        // 一致程度 x 长度 (一致直线的总长度达到图像长边时为满分)
+        let agreement = agreeing_votes as f32 / total_votes as f32;
+        let strength = (agreeing_votes as f32 / image.width.max(image.height) as f32).min(1.0);
+
+        Some(StraightenSuggestion {
+            angle: -tilt,
+            confidence: (agreement * strength).clamp(0.0, 1.0),
+            lines: agreeing,
+        })
+    }
+}
+
+/// 边缘像素：位置和梯度方向 (弧度)
+struct Edge {
+    x: f32,
+    y: f32,
+    direction: f32,
+}
+
+/// Sobel 梯度 + 沿梯度方向的非极大值抑制，得到细化的边缘
+fn detect_edges(image: &Image) -> Vec<Edge> {
+    let (width, height) = (image.width as usize, image.height as usize);
+    let gray: Vec<f32> = image
+        .data
+        .chunks_exact(4)
+        .map(|p| (LUMA[0] * p[0] as f32 + LUMA[1] * p[1] as f32 + LUMA[2] * p[2] as f32) / 255.0)
+        .collect();
+
+    let mut gradients = vec![(0.0f32, 0.0f32); width * height];
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let at = |dx: isize, dy: isize| {
+                gray[(y as isize + dy) as usize * width + (x as isize + dx) as usize]
+            };
+            let gx =
+                at(1, -1) + 2.0 * at(1, 0) + at(1, 1) - at(-1, -1) - 2.0 * at(-1, 0) - at(-1, 1);
+            let gy =
+                at(-1, 1) + 2.0 * at(0, 1) + at(1, 1) - at(-1, -1) - 2.0 * at(0, -1) - at(1, -1);
+            gradients[y * width + x] = (gx, gy);
+        }
+    }
+
+    let magnitude = |(gx, gy): (f32, f32)| gx.hypot(gy);
+    let max = gradients.iter().copied().map(magnitude).fold(0.0, f32::max);
+    if max <= 0.0 {
+        return Vec::new();
+    }
+    let threshold = EDGE_THRESHOLD * max;
+
+    let mut edges = Vec::new();
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let gradient = gradients[y * width + x];
+            let strength = magnitude(gradient);
+            if strength < threshold {
+                continue;
+            }
+            // 沿梯度方向的两个邻居都不更强时才保留
+            let direction = gradient.1.atan2(gradient.0);
+            let (dx, dy) = (
+                direction.cos().round() as isize,
+                direction.sin().round() as isize,
+            );
+            let neighbor = |sign: isize| {
+                let nx = (x as isize + sign * dx) as usize;
+                let ny = (y as isize + sign * dy) as usize;
+                magnitude(gradients[ny * width + nx])
+            };
+            if strength >= neighbor(1) && strength >= neighbor(-1) {
+                edges.push(Edge {
+                    x: x as f32,
+                    y: y as f32,
+                    direction,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// 在接近水平或竖直的角度范围内做霍夫变换，返回最强的几条直线
+///
+/// 直线表示为 `rho = x cos(theta) + y sin(theta)`，`theta` 是法线方向；
+/// 水平线的法线接近 90 度，竖直线的法线接近 0 度。
+fn hough_lines(
+    edges: &[Edge],
+    width: u32,
+    height: u32,
+    orientation: LineOrientation,
+) -> Vec<DetectedLine> {
+    let normal = match orientation {
+        LineOrientation::Horizontal => 90.0f32,
+        LineOrientation::Vertical => 0.0,
+    };
+    let steps = (2.0 * MAX_TILT / THETA_STEP).round() as usize + 1;
+    let thetas: Vec<(f32, f32)> = (0..steps)
+        .map(|i| {
+            let theta = (normal - MAX_TILT + i as f32 * THETA_STEP).to_radians();
+            (theta.cos(), theta.sin())
+        })
+        .collect();
+    let diagonal = (width as f32).hypot(height as f32).ceil() as usize;
+    let rhos = 2 * diagonal + 1;
+    let mut accumulator = vec![0u32; steps * rhos];
+
+    // 只有梯度方向 (即直线法线) 在角度范围附近的边缘参与投票
+    let tolerance = (MAX_TILT + 5.0).to_radians();
+    let normal_radians = normal.to_radians();
+    for edge in edges {
+        let mut offset = (edge.direction - normal_radians).rem_euclid(std::f32::consts::PI);
+        if offset > std::f32::consts::FRAC_PI_2 {
+            offset -= std::f32::consts::PI;
+        }
+        if offset.abs() > tolerance {
+            continue;
+        }
+        for (i, (cos, sin)) in thetas.iter().enumerate() {
+            let rho = (edge.x * cos + edge.y * sin).round() as isize + diagonal as isize;
+            accumulator[i * rhos + rho as usize] += 1;
+        }
+    }
+
+    // 逐个取最大值，并抑制其邻域 (角度 ±1 度，距离 ±5 像素)
+    let theta_window = (1.0 / THETA_STEP) as usize;
+    let rho_window = 5;
+    let mut lines = Vec::new();
+    while lines.len() < MAX_LINES {
+        let Some((index, &votes)) = accumulator
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, votes)| **votes)
+        else {
+            break;
+        };
+        if votes == 0 {
+            break;
+        }
+        let (theta_index, rho_index) = (index / rhos, index % rhos);
+        lines.push(DetectedLine {
+            orientation,
+            tilt: theta_index as f32 * THETA_STEP - MAX_TILT,
+            votes,
+        });
+        for t in
+            theta_index.saturating_sub(theta_window)..(theta_index + theta_window + 1).min(steps)
+        {
+            for r in rho_index.saturating_sub(rho_window)..(rho_index + rho_window + 1).min(rhos) {
+                accumulator[t * rhos + r] = 0;
+            }
+        }
+    }
+    lines
+}
@@ -1,7 +1,11 @@
 //! 图像变换模块
 
 use super::Image;
-use editor_studio_params::CropRect;
+use editor_studio_math::{GeometryPlan, Homography};
+use editor_studio_params::{CropRect, CropTransform, EditOperation};
+
+/// 每个方向上最多的超采样次数
+const MAX_SUPERSAMPLES: u32 = 4;
 
 /// 图像变换操作
 pub struct ImageTransform;
@@ -14,7 +18,8 @@ impl ImageTransform {
         let width = (crop_rect.width * image.width as f32) as u32;
         let height = (crop_rect.height * image.height as f32) as u32;
 
-        let mut result = Image::new(width, height, image.format);
+        let mut result =
+            Image::new(width, height, image.format).with_color_space(image.color_space);
 
         for row in 0..height {
             for col in 0..width {
@@ -53,7 +58,8 @@ impl ImageTransform {
             }
         };
 
-        let mut result = Image::new(new_width, new_height, image.format);
+        let mut result =
+            Image::new(new_width, new_height, image.format).with_color_space(image.color_space);
 
         // 简单的双线性插值
         let x_ratio = image.width as f32 / new_width as f32;
@@ -76,28 +82,92 @@ impl ImageTransform {
         result
     }
 
-    /// 旋转图像
+    /// 旋转图像 (度，顺时针)，输出尺寸扩大到能容纳整张旋转后的图像
     pub fn rotate(image: &Image, degrees: f32) -> Image {
-        // TODO: 实现旋转
-        image.clone()
+        Self::apply_operations(image, &[EditOperation::Rotate { degrees }])
     }
 
     /// 翻转图像
     pub fn flip(image: &Image, horizontal: bool, vertical: bool) -> Image {
-        let mut result = Image::new(image.width, image.height, image.format);
+        Self::apply_operations(
+            image,
+            &[EditOperation::Flip {
+                horizontal,
+                vertical,
+            }],
+        )
+    }
 
-        for y in 0..image.height {
-            for x in 0..image.width {
-                let src_x = if horizontal { image.width - 1 - x } else { x };
-                let src_y = if vertical { image.height - 1 - y } else { y };
+    /// 拉直裁剪：旋转和裁剪在一次重采样中完成
+    pub fn crop_rotated(image: &Image, crop: &CropTransform) -> Image {
+        Self::apply_operations(image, &[EditOperation::StraightenCrop(*crop)])
+    }
 
-                let src_idx = (src_y * image.width + src_x) as usize * 4;
-                let dst_idx = (y * image.width + x) as usize * 4;
+    /// 四点透视校正：输入图像中的四边形 (左上、右上、右下、左下，归一化坐标) 被拉伸为矩形
+    pub fn warp_perspective(image: &Image, corners: &[[f32; 2]; 4]) -> Image {
+        Self::apply_operations(image, &[EditOperation::Perspective { corners: *corners }])
+    }
 
-                result.data[dst_idx] = image.data[src_idx];
-                result.data[dst_idx + 1] = image.data[src_idx + 1];
-                result.data[dst_idx + 2] = image.data[src_idx + 2];
-                result.data[dst_idx + 3] = image.data[src_idx + 3];
+    /// 梯形校正 (-1.0 到 1.0)，输出尺寸与输入相同
+    pub fn keystone(image: &Image, vertical: f32, horizontal: f32) -> Image {
+        Self::apply_operations(
+            image,
+            &[EditOperation::Keystone {
+                vertical,
+                horizontal,
+            }],
+        )
+    }
+
+    /// 按顺序应用几何操作
+    ///
+    /// 所有操作先合成为一个变换，只对原图重采样一次，
+    /// 不会因为操作数量增加而累积插值损失。
+    pub fn apply_operations(image: &Image, operations: &[EditOperation]) -> Image {
+        if operations.iter().all(EditOperation::is_identity) {
+            return image.clone();
+        }
+        Self::resample(image, &GeometryPlan::new(&image.size(), operations))
+    }
+
+    /// 按几何变换计划重采样 (双线性插值，原图之外为透明)
+    ///
+    /// 输出像素覆盖多个原图像素时 (透视校正中被压缩的一侧) 按覆盖范围超采样，避免锯齿。
+    pub fn resample(image: &Image, plan: &GeometryPlan) -> Image {
+        Self::resample_region(image, plan, 0, 0, plan.output.width, plan.output.height)
+    }
+
+    /// 只重采样输出图像中的一个区域 (输出像素坐标)
+    pub fn resample_region(
+        image: &Image,
+        plan: &GeometryPlan,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Image {
+        let mut result =
+            Image::new(width, height, image.format).with_color_space(image.color_space);
+        let to_source = &plan.to_source;
+        // 仿射变换的覆盖范围处处相同，只需计算一次
+        let affine = to_source
+            .is_affine()
+            .then(|| footprint(to_source, 0.5, 0.5));
+
+        for row in 0..height {
+            for col in 0..width {
+                let px = (x + col) as f32 + 0.5;
+                let py = (y + row) as f32 + 0.5;
+                let (nx, ny) = affine.unwrap_or_else(|| footprint(to_source, px, py));
+                let pixel = if nx == 1 && ny == 1 {
+                    // 像素中心映射到原图，再换算到以像素中心为整数的坐标
+                    let (sx, sy) = to_source.apply(px, py);
+                    sample_bilinear(image, sx - 0.5, sy - 0.5)
+                } else {
+                    sample_area(image, to_source, px, py, nx, ny)
+                };
+                let dst_idx = (row * width + col) as usize * 4;
+                result.data[dst_idx..dst_idx + 4].copy_from_slice(&pixel);
             }
         }
 
@@ -105,6 +175,48 @@ impl ImageTransform {
     }
 }
 
+/// 输出像素 `(x, y)` 在原图中覆盖的范围对应的超采样次数 (水平, 竖直)
+fn footprint(to_source: &Homography, x: f32, y: f32) -> (u32, u32) {
+    let (sx, sy) = to_source.apply(x, y);
+    let (rx, ry) = to_source.apply(x + 1.0, y);
+    let (dx, dy) = to_source.apply(x, y + 1.0);
+    // 留出一点余量，纯旋转的浮点误差不会触发超采样；无穷远的点得到 NaN，按 1 处理
+    let samples = |length: f32| ((length - 0.01).ceil() as u32).clamp(1, MAX_SUPERSAMPLES);
+    (
+        samples((rx - sx).hypot(ry - sy)),
+        samples((dx - sx).hypot(dy - sy)),
+    )
+}
+
+/// 在输出像素 `(x, y)` 内均匀取 `nx` x `ny` 个点，按透明度加权平均
+fn sample_area(image: &Image, to_source: &Homography, x: f32, y: f32, nx: u32, ny: u32) -> [u8; 4] {
+    let mut sum = [0.0f32; 4];
+    for j in 0..ny {
+        for i in 0..nx {
+            let (sx, sy) = to_source.apply(
+                x - 0.5 + (i as f32 + 0.5) / nx as f32,
+                y - 0.5 + (j as f32 + 0.5) / ny as f32,
+            );
+            let sample = sample_bilinear(image, sx - 0.5, sy - 0.5);
+            let alpha = sample[3] as f32;
+            for c in 0..3 {
+                sum[c] += sample[c] as f32 * alpha;
+            }
+            sum[3] += alpha;
+        }
+    }
+    if sum[3] <= 0.0 {
+        return [0; 4];
+    }
+    let count = (nx * ny) as f32;
+    [
+        (sum[0] / sum[3]).round() as u8,
+        (sum[1] / sum[3]).round() as u8,
+        (sum[2] / sum[3]).round() as u8,
+        (sum[3] / count).round() as u8,
+    ]
+}
+
 /// 调整大小选项
 pub enum ResizeOptions {
     /// 精确尺寸
@@ -114,3 +226,35 @@ pub enum ResizeOptions {
     /// 填充容器
     Cover { width: u32, height: u32 },
 }
+
+/// 双线性采样，坐标以像素中心为整数；超出原图半个像素以上的位置为透明
+pub(crate) fn sample_bilinear(image: &Image, x: f32, y: f32) -> [u8; 4] {
+    let max_x = image.width as f32 - 0.5;
+    let max_y = image.height as f32 - 0.5;
+    if x < -0.5 || y < -0.5 || x > max_x || y > max_y {
+        return [0; 4];
+    }
+
+    let x = x.clamp(0.0, image.width as f32 - 1.0);
+    let y = y.clamp(0.0, image.height as f32 - 1.0);
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(image.width - 1);
+    let y1 = (y0 + 1).min(image.height - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let pixel = |px: u32, py: u32| {
+        let idx = (py * image.width + px) as usize * 4;
+        &image.data[idx..idx + 4]
+    };
+    let (p00, p10, p01, p11) = (pixel(x0, y0), pixel(x1, y0), pixel(x0, y1), pixel(x1, y1));
+
+    let mut result = [0u8; 4];
+    for (c, value) in result.iter_mut().enumerate() {
+        let top = p00[c] as f32 * (1.0 - fx) + p10[c] as f32 * fx;
+        let bottom = p01[c] as f32 * (1.0 - fx) + p11[c] as f32 * fx;
+        *value = (top * (1.0 - fy) + bottom * fy).round() as u8;
+    }
+    result
+}
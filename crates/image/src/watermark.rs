@@ -0,0 +1,331 @@
+//! 导出水印
+//!
+//! 水印 (PNG 标志或文字) 只在导出时作为最后一步叠加，不属于编辑状态，
+//! 也不会出现在可编辑的预览中。水印的大小和边距都相对于输出图像计算，
+//! 同一水印设置在不同分辨率的导出中保持相同的比例。
+
+use super::adjust::{srgb_decode_table, srgb_encode};
+use super::transform::sample_bilinear;
+use super::{ColorManager, Image, ImageFormat, Proxy};
+use ab_glyph::{Font, FontArc, PxScale, ScaleFont};
+use editor_studio_params::ColorSpace;
+
+/// 测量文字宽度时使用的字号 (像素)
+const MEASURE_SIZE: f32 = 100.0;
+
+/// 水印锚点
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WatermarkAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+}
+
+impl WatermarkAnchor {
+    /// 从名称解析 (如 `"bottom-right"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left" => Some(WatermarkAnchor::TopLeft),
+            "top" => Some(WatermarkAnchor::Top),
+            "top-right" => Some(WatermarkAnchor::TopRight),
+            "left" => Some(WatermarkAnchor::Left),
+            "center" => Some(WatermarkAnchor::Center),
+            "right" => Some(WatermarkAnchor::Right),
+            "bottom-left" => Some(WatermarkAnchor::BottomLeft),
+            "bottom" => Some(WatermarkAnchor::Bottom),
+            "bottom-right" => Some(WatermarkAnchor::BottomRight),
+            _ => None,
+        }
+    }
+
+    /// 水平和竖直方向的位置 (0.0 左/上，0.5 居中，1.0 右/下)
+    fn factors(&self) -> (f32, f32) {
+        match self {
+            WatermarkAnchor::TopLeft => (0.0, 0.0),
+            WatermarkAnchor::Top => (0.5, 0.0),
+            WatermarkAnchor::TopRight => (1.0, 0.0),
+            WatermarkAnchor::Left => (0.0, 0.5),
+            WatermarkAnchor::Center => (0.5, 0.5),
+            WatermarkAnchor::Right => (1.0, 0.5),
+            WatermarkAnchor::BottomLeft => (0.0, 1.0),
+            WatermarkAnchor::Bottom => (0.5, 1.0),
+            WatermarkAnchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// 水印内容
+#[derive(Clone)]
+pub enum WatermarkContent {
+    /// 图像 (通常是带透明通道的 PNG 标志)
+    Image(Image),
+    /// 用 TTF/OTF 字体渲染的文字，可以有多行
+    Text {
+        text: String,
+        font: FontArc,
+        /// 文字颜色 (sRGB)
+        color: [u8; 3],
+    },
+}
+
+/// 导出水印
+#[derive(Clone)]
+pub struct Watermark {
+    /// 水印内容
+    pub content: WatermarkContent,
+    /// 锚点 (平铺时忽略)
+    pub anchor: WatermarkAnchor,
+    /// 到图像边缘的距离，相对于图像短边 (0.0 到 0.5)
+    pub margin: f32,
+    /// 不透明度 (0.0 到 1.0)
+    pub opacity: f32,
+    /// 水印宽度占图像宽度的比例 (0.01 到 1.0)
+    pub scale: f32,
+    /// 是否平铺满整张图像
+    pub tiled: bool,
+    /// 平铺时水印之间的间距，相对于水印尺寸
+    pub spacing: f32,
+}
+
+impl Watermark {
+    /// 图像水印，默认位于右下角
+    pub fn image(image: Image) -> Self {
+        Self::new(WatermarkContent::Image(image))
+    }
+
+    /// 文字水印，默认位于右下角
+    ///
+    /// # 参数
+    /// * `font_data` - TTF 或 OTF 字体文件的内容
+    /// * `color` - 文字颜色 (sRGB)
+    pub fn text(text: &str, font_data: Vec<u8>, color: [u8; 3]) -> Result<Self, String> {
+        if text.trim().is_empty() {
+            return Err("Watermark text is empty".to_string());
+        }
+        let font = FontArc::try_from_vec(font_data).map_err(|e| format!("Invalid font: {}", e))?;
+        Ok(Self::new(WatermarkContent::Text {
+            text: text.to_string(),
+            font,
+            color,
+        }))
+    }
+
+    fn new(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            anchor: WatermarkAnchor::default(),
+            margin: 0.03,
+            opacity: 0.8,
+            scale: 0.2,
+            tiled: false,
+            spacing: 0.5,
+        }
+    }
+
+    /// 设置锚点、边距和大小
+    pub fn with_placement(mut self, anchor: WatermarkAnchor, margin: f32, scale: f32) -> Self {
+        self.anchor = anchor;
+        self.margin = margin.clamp(0.0, 0.5);
+        self.scale = scale.clamp(0.01, 1.0);
+        self
+    }
+
+    /// 设置不透明度
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置平铺
+    pub fn with_tiling(mut self, tiled: bool, spacing: f32) -> Self {
+        self.tiled = tiled;
+        self.spacing = spacing.clamp(0.0, 10.0);
+        self
+    }
+
+    /// 把水印叠加到图像上
+    pub fn apply(&self, image: &Image) -> Image {
+        let mut result = image.clone();
+        let target_width = (self.scale * image.width as f32).max(1.0);
+        let Some(mark) = self.render(target_width) else {
+            return result;
+        };
+        let mark = ColorManager::convert(&mark, image.color_space);
+        let width = target_width;
+        let height = width * mark.height as f32 / mark.width as f32;
+
+        let (image_width, image_height) = (image.width as f32, image.height as f32);
+        let positions = if self.tiled {
+            let step_x = width * (1.0 + self.spacing);
+            let step_y = height * (1.0 + self.spacing);
+            // 网格以图像中心为基准，保证四周对称
+            let start_x = ((image_width - width) / 2.0).rem_euclid(step_x) - step_x;
+            let start_y = ((image_height - height) / 2.0).rem_euclid(step_y) - step_y;
+            let columns = ((image_width - start_x) / step_x).ceil() as usize;
+            let rows = ((image_height - start_y) / step_y).ceil() as usize;
+            (0..rows)
+                .flat_map(|row| {
+                    (0..columns).map(move |column| {
+                        (
+                            start_x + column as f32 * step_x,
+                            start_y + row as f32 * step_y,
+                        )
+                    })
+                })
+                .collect()
+        } else {
+            let margin = self.margin * image_width.min(image_height);
+            let (ax, ay) = self.anchor.factors();
+            vec![(
+                margin + ax * (image_width - width - 2.0 * margin),
+                margin + ay * (image_height - height - 2.0 * margin),
+            )]
+        };
+
+        for (x, y) in positions {
+            self.stamp(&mut result, &mark, x, y, width, height);
+        }
+        result
+    }
+
+    /// 生成宽度约为 `target_width` 的水印图像，文字按目标大小直接光栅化
+    fn render(&self, target_width: f32) -> Option<Image> {
+        match &self.content {
+            WatermarkContent::Image(image) => {
+                if image.width == 0 || image.height == 0 {
+                    return None;
+                }
+                // 大幅缩小时先做盒式滤波，避免双线性采样产生锯齿
+                let height = target_width * image.height as f32 / image.width as f32;
+                let max_dimension = (2.0 * target_width.max(height)).ceil() as u32;
+                Some(Proxy::new(image, max_dimension).image)
+            }
+            WatermarkContent::Text { text, font, color } => {
+                let measured = measure_text(font, text, MEASURE_SIZE).0;
+                if measured <= 0.0 {
+                    return None;
+                }
+                Some(render_text(
+                    font,
+                    text,
+                    MEASURE_SIZE * target_width / measured,
+                    *color,
+                ))
+            }
+        }
+    }
+
+    /// 把水印绘制到 `(x, y)` 处、大小为 `width` x `height` 的矩形中
+    fn stamp(&self, image: &mut Image, mark: &Image, x: f32, y: f32, width: f32, height: f32) {
+        let decode = srgb_decode_table();
+        let x0 = x.floor().max(0.0) as u32;
+        let y0 = y.floor().max(0.0) as u32;
+        let x1 = ((x + width).ceil().max(0.0) as u32).min(image.width);
+        let y1 = ((y + height).ceil().max(0.0) as u32).min(image.height);
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let u = (px as f32 + 0.5 - x) / width;
+                let v = (py as f32 + 0.5 - y) / height;
+                if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+                    continue;
+                }
+                let top = sample_bilinear(
+                    mark,
+                    u * mark.width as f32 - 0.5,
+                    v * mark.height as f32 - 0.5,
+                );
+                let alpha = top[3] as f32 / 255.0 * self.opacity;
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                let index = (py * image.width + px) as usize * 4;
+                let pixel = &mut image.data[index..index + 4];
+                for c in 0..3 {
+                    let base = decode[pixel[c] as usize];
+                    let value = decode[top[c] as usize];
+                    pixel[c] = srgb_encode(base + (value - base) * alpha);
+                }
+                let base_alpha = pixel[3] as f32 / 255.0;
+                pixel[3] = ((base_alpha + alpha * (1.0 - base_alpha)) * 255.0).round() as u8;
+            }
+        }
+    }
+}
+
+/// 多行文字在指定字号下的宽度和高度 (像素)
+fn measure_text(font: &FontArc, text: &str, size: f32) -> (f32, f32) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let width = text
+        .lines()
+        .map(|line| {
+            layout_line(font, line, size)
+                .last()
+                .map_or(0.0, |g| g.1 + scaled.h_advance(g.0))
+        })
+        .fold(0.0, f32::max);
+    let lines = text.lines().count().max(1) as f32;
+    let height = lines * scaled.height() + (lines - 1.0) * scaled.line_gap();
+    (width, height)
+}
+
+/// 单行文字的字形及其水平位置 (含字距调整)
+fn layout_line(font: &FontArc, line: &str, size: f32) -> Vec<(ab_glyph::GlyphId, f32)> {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut caret = 0.0;
+    let mut previous = None;
+    let mut glyphs = Vec::new();
+    for c in line.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        glyphs.push((id, caret));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    glyphs
+}
+
+/// 把文字光栅化为透明背景的图像 (sRGB)
+fn render_text(font: &FontArc, text: &str, size: f32, color: [u8; 3]) -> Image {
+    let (width, height) = measure_text(font, text, size);
+    let (width, height) = (width.ceil().max(1.0) as u32, height.ceil().max(1.0) as u32);
+    let mut image = Image::new(width, height, ImageFormat::Png).with_color_space(ColorSpace::Srgb);
+    for pixel in image.data.chunks_exact_mut(4) {
+        pixel[..3].copy_from_slice(&color);
+        pixel[3] = 0;
+    }
+
+    let scaled = font.as_scaled(PxScale::from(size));
+    let line_height = scaled.height() + scaled.line_gap();
+    for (row, line) in text.lines().enumerate() {
+        let baseline = scaled.ascent() + row as f32 * line_height;
+        for (id, x) in layout_line(font, line, size) {
+            let glyph = id.with_scale_and_position(size, ab_glyph::point(x, baseline));
+            let Some(outline) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+                    return;
+                }
+                let index = (py as u32 * width + px as u32) as usize * 4 + 3;
+                let alpha = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                image.data[index] = image.data[index].max(alpha);
+            });
+        }
+    }
+    image
+}
@@ -0,0 +1,182 @@
+//! 画笔蒙版栅格化
+//!
+//! 笔触以原图归一化坐标保存，这里按指定分辨率栅格化为 8 位覆盖程度。
+//! 预览时按代理分辨率栅格化，导出时按原图分辨率重新栅格化。
+
+use editor_studio_params::BrushStroke;
+
+/// 栅格化后的蒙版
+///
+/// 只保存笔触覆盖的区域，导出时按原图分辨率栅格化也不需要整幅图像大小的缓冲区。
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaskRaster {
+    /// 栅格化分辨率
+    pub width: u32,
+    pub height: u32,
+    /// 笔触覆盖的区域 (像素坐标，左、上、右、下，不含右下边界)，区域外覆盖程度为 0
+    pub bounds: [u32; 4],
+    /// 区域内的覆盖程度 (0-255)，逐行存储
+    pub data: Vec<u8>,
+}
+
+impl MaskRaster {
+    /// 按顺序栅格化笔触
+    pub fn from_strokes(strokes: &[BrushStroke], width: u32, height: u32) -> Self {
+        let bounds = stroke_bounds(strokes, width, height);
+        let [left, top, right, bottom] = bounds;
+        let mut coverage = vec![0.0f32; ((right - left) * (bottom - top)) as usize];
+        for stroke in strokes {
+            rasterize_stroke(&mut coverage, bounds, width, height, stroke);
+        }
+        Self {
+            width,
+            height,
+            bounds,
+            data: coverage
+                .iter()
+                .map(|value| (value * 255.0).round() as u8)
+                .collect(),
+        }
+    }
+
+    /// 像素 `(x, y)` 的覆盖程度 (0-255)
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        let [left, top, right, bottom] = self.bounds;
+        if x < left || x >= right || y < top || y >= bottom {
+            return 0;
+        }
+        self.data[((y - top) * (right - left) + (x - left)) as usize]
+    }
+
+    /// 在归一化坐标处双线性采样覆盖程度 (0.0 到 1.0)
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let x = (u * self.width as f32 - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let y = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let x0 = x.floor() as u32;
+        let y0 = y.floor() as u32;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let fx = x - x0 as f32;
+        let fy = y - y0 as f32;
+
+        let at = |px: u32, py: u32| self.get(px, py) as f32 / 255.0;
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// 所有绘制笔触 (不含擦除) 覆盖的像素区域，没有笔触时为空区域
+fn stroke_bounds(strokes: &[BrushStroke], width: u32, height: u32) -> [u32; 4] {
+    let (mut left, mut top) = (f32::INFINITY, f32::INFINITY);
+    let (mut right, mut bottom) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for stroke in strokes.iter().filter(|stroke| !stroke.erase) {
+        let radius = stroke.size * 0.5 * height as f32;
+        if radius <= 0.0 || stroke.flow <= 0.0 {
+            continue;
+        }
+        for &[u, v] in &stroke.points {
+            let (x, y) = (u * width as f32, v * height as f32);
+            left = left.min(x - radius);
+            top = top.min(y - radius);
+            right = right.max(x + radius);
+            bottom = bottom.max(y + radius);
+        }
+    }
+    if left > right || top > bottom {
+        return [0; 4];
+    }
+    // 浮点数到整数的转换是饱和的，越界和 NaN 都会落在图像内
+    let left = (left.floor() as u32).min(width);
+    let top = (top.floor() as u32).min(height);
+    [
+        left,
+        top,
+        (right.ceil() as u32).clamp(left, width),
+        (bottom.ceil() as u32).clamp(top, height),
+    ]
+}
+
+/// 沿笔触路径等间距盖章 (只写入 `bounds` 区域)
+fn rasterize_stroke(
+    coverage: &mut [f32],
+    bounds: [u32; 4],
+    width: u32,
+    height: u32,
+    stroke: &BrushStroke,
+) {
+    let radius = stroke.size * 0.5 * height as f32;
+    if stroke.points.is_empty() || radius <= 0.0 || stroke.flow <= 0.0 || coverage.is_empty() {
+        return;
+    }
+
+    let to_pixels = |[u, v]: [f32; 2]| (u * width as f32, v * height as f32);
+    let spacing = (radius * 0.25).max(0.5);
+
+    let mut previous = to_pixels(stroke.points[0]);
+    stamp(coverage, bounds, previous, radius, stroke);
+    let mut carried = 0.0;
+    for &point in &stroke.points[1..] {
+        let current = to_pixels(point);
+        let (dx, dy) = (current.0 - previous.0, current.1 - previous.1);
+        let length = (dx * dx + dy * dy).sqrt();
+
+        // 从上一段剩余的距离开始，保持印记间距均匀
+        let mut t = spacing - carried;
+        while t <= length {
+            let f = t / length;
+            stamp(
+                coverage,
+                bounds,
+                (previous.0 + dx * f, previous.1 + dy * f),
+                radius,
+                stroke,
+            );
+            t += spacing;
+        }
+        carried = length - (t - spacing);
+        previous = current;
+    }
+}
+
+/// 盖一个圆形笔刷印记 (`coverage` 只包含 `bounds` 区域)
+fn stamp(
+    coverage: &mut [f32],
+    [left, top, right, bottom]: [u32; 4],
+    (cx, cy): (f32, f32),
+    radius: f32,
+    stroke: &BrushStroke,
+) {
+    let x0 = ((cx - radius).floor().max(0.0) as u32).max(left);
+    let y0 = ((cy - radius).floor().max(0.0) as u32).max(top);
+    let x1 = ((cx + radius).ceil().max(0.0) as u32).min(right);
+    let y1 = ((cy + radius).ceil().max(0.0) as u32).min(bottom);
+    let inner = 1.0 - stroke.feather;
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            let d = (dx * dx + dy * dy).sqrt() / radius;
+            if d >= 1.0 {
+                continue;
+            }
+            let falloff = if d <= inner {
+                1.0
+            } else {
+                let t = (d - inner) / stroke.feather;
+                1.0 - t * t * (3.0 - 2.0 * t)
+            };
+            let alpha = stroke.flow * falloff;
+            let value = &mut coverage[((y - top) * (right - left) + (x - left)) as usize];
+            if stroke.erase {
+                *value *= 1.0 - alpha;
+            } else {
+                *value += (1.0 - *value) * alpha;
+            }
+        }
+    }
+}
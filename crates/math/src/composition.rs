@@ -0,0 +1,357 @@
+//! 构图规则
+//!
+//! 每条规则按主体在裁剪区域中的位置给出 0.0 到 1.0 的评分，不适用的规则
+//! (如画面中没有人脸时的头顶留白) 返回 None。[`WeightedComposition`] 按权重组合多条规则，
+//! 裁剪建议引擎用它来评价构图，不同场景可以使用不同的组合。
+
+use super::rule_of_thirds_score;
+use editor_studio_params::CropRect;
+use std::fmt;
+use std::sync::Arc;
+
+/// 黄金分割点 (1 / phi)
+const GOLDEN: f32 = 0.618_034;
+/// 人脸上方留白的理想范围 (相对于裁剪高度)
+const HEADROOM_RANGE: (f32, f32) = (0.05, 0.2);
+/// 主体朝向一侧的空间占两侧空间之和的理想比例
+const LEAD_ROOM: f32 = 0.65;
+
+/// 主体类型
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubjectKind {
+    /// 一般物体
+    #[default]
+    Object,
+    /// 人脸 (头顶需要留白)
+    Face,
+}
+
+/// 画面中的主体
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Subject {
+    /// 边界框 (归一化坐标)
+    pub rect: CropRect,
+    pub kind: SubjectKind,
+    /// 主体朝向 (图像坐标中的方向向量，如向右为 `[1, 0]`)，未知时为 None
+    pub facing: Option<[f32; 2]>,
+    /// 重要程度 (0.0 到 1.0)，次要主体可以被排除在裁剪之外，但不应被切开
+    pub importance: f32,
+}
+
+impl Subject {
+    /// 一般物体
+    pub fn new(rect: CropRect) -> Self {
+        Self {
+            rect,
+            kind: SubjectKind::Object,
+            facing: None,
+            importance: 1.0,
+        }
+    }
+
+    /// 人脸
+    pub fn face(rect: CropRect) -> Self {
+        Self {
+            kind: SubjectKind::Face,
+            ..Self::new(rect)
+        }
+    }
+
+    /// 设置主体朝向
+    pub fn with_facing(mut self, x: f32, y: f32) -> Self {
+        let length = x.hypot(y);
+        self.facing = (length > 0.0).then(|| [x / length, y / length]);
+        self
+    }
+
+    /// 设置重要程度 (0.0 到 1.0)
+    pub fn with_importance(mut self, importance: f32) -> Self {
+        self.importance = importance.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 中心 (归一化坐标)
+    pub fn center(&self) -> (f32, f32) {
+        (
+            self.rect.x + self.rect.width / 2.0,
+            self.rect.y + self.rect.height / 2.0,
+        )
+    }
+}
+
+/// 构图规则
+pub trait CompositionRule {
+    /// 规则名称
+    fn name(&self) -> &str;
+
+    /// 裁剪区域的构图评分 (0.0 到 1.0)，规则不适用于这些主体时返回 None
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32>;
+}
+
+/// 三分法则：主体中心靠近三分线交点
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RuleOfThirds;
+
+impl CompositionRule for RuleOfThirds {
+    fn name(&self) -> &str {
+        "rule-of-thirds"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        Some(rule_of_thirds_score(crop, &subject_union(subjects)?))
+    }
+}
+
+/// 黄金分割：主体中心靠近黄金分割线交点
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GoldenRatio;
+
+impl CompositionRule for GoldenRatio {
+    fn name(&self) -> &str {
+        "golden-ratio"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let (u, v) = relative_center(crop, &subject_union(subjects)?);
+        let lines = [1.0 - GOLDEN, GOLDEN];
+        let distance = lines
+            .iter()
+            .flat_map(|&x| lines.iter().map(move |&y| (u - x).hypot(v - y)))
+            .fold(f32::MAX, f32::min);
+        Some((1.0 - distance / 0.5).max(0.0))
+    }
+}
+
+/// 居中对称：主体位于画面中心，水平方向的偏移比竖直方向更敏感
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Centered;
+
+impl CompositionRule for Centered {
+    fn name(&self) -> &str {
+        "centered"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let (u, v) = relative_center(crop, &subject_union(subjects)?);
+        let distance = (2.0 * (u - 0.5)).hypot(v - 0.5);
+        Some((1.0 - distance / 0.5).max(0.0))
+    }
+}
+
+/// 对角线构图：主体中心靠近画面的对角线
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Diagonal;
+
+impl CompositionRule for Diagonal {
+    fn name(&self) -> &str {
+        "diagonal"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let (u, v) = relative_center(crop, &subject_union(subjects)?);
+        // 到两条对角线的距离，最远 (边的中点) 为 0.5 / sqrt(2)
+        let distance = (u - v).abs().min((u + v - 1.0).abs()) / std::f32::consts::SQRT_2;
+        let farthest = 0.5 / std::f32::consts::SQRT_2;
+        Some((1.0 - distance / farthest).max(0.0))
+    }
+}
+
+/// 头顶留白：人脸上方保留适当的空间，切到头顶得 0 分
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Headroom;
+
+impl CompositionRule for Headroom {
+    fn name(&self) -> &str {
+        "headroom"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let (low, high) = HEADROOM_RANGE;
+        let scores: Vec<f32> = subjects
+            .iter()
+            .filter(|subject| subject.kind == SubjectKind::Face)
+            .map(|face| {
+                let gap = (face.rect.y - crop.y) / crop.height;
+                if gap < 0.0 {
+                    0.0
+                } else if gap < low {
+                    gap / low
+                } else if gap <= high {
+                    1.0
+                } else {
+                    // 留白过多时逐渐降低，到半个画面时为 0
+                    (1.0 - (gap - high) / (0.5 - high)).max(0.0)
+                }
+            })
+            .collect();
+        mean(&scores)
+    }
+}
+
+/// 视线空间：主体朝向的一侧比背后留出更多空间
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LeadRoom;
+
+impl CompositionRule for LeadRoom {
+    fn name(&self) -> &str {
+        "lead-room"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let scores: Vec<f32> = subjects
+            .iter()
+            .filter_map(|subject| {
+                let [dx, dy] = subject.facing?;
+                let rect = &subject.rect;
+                // 每个方向上主体前后的空间
+                let left = rect.x - crop.x;
+                let right = crop.x + crop.width - (rect.x + rect.width);
+                let top = rect.y - crop.y;
+                let bottom = crop.y + crop.height - (rect.y + rect.height);
+                let share = |front: f32, back: f32| {
+                    let (front, back) = (front.max(0.0), back.max(0.0));
+                    if front + back <= 0.0 {
+                        0.5
+                    } else {
+                        front / (front + back)
+                    }
+                };
+                let horizontal = if dx >= 0.0 {
+                    share(right, left)
+                } else {
+                    share(left, right)
+                };
+                let vertical = if dy >= 0.0 {
+                    share(bottom, top)
+                } else {
+                    share(top, bottom)
+                };
+                let share = (horizontal * dx.abs() + vertical * dy.abs()) / (dx.abs() + dy.abs());
+                Some((share / LEAD_ROOM).min(1.0))
+            })
+            .collect();
+        mean(&scores)
+    }
+}
+
+/// 按权重组合的构图规则，不适用的规则不参与加权
+#[derive(Clone, Default)]
+pub struct WeightedComposition {
+    rules: Vec<(Arc<dyn CompositionRule + Send + Sync>, f32)>,
+}
+
+impl WeightedComposition {
+    /// 空组合 (没有规则时评分为 None)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一条规则
+    pub fn with(mut self, rule: impl CompositionRule + Send + Sync + 'static, weight: f32) -> Self {
+        if weight > 0.0 {
+            self.rules.push((Arc::new(rule), weight));
+        }
+        self
+    }
+
+    /// 通用构图：三分法则为主，兼顾黄金分割、头顶留白和视线空间
+    pub fn balanced() -> Self {
+        Self::new()
+            .with(RuleOfThirds, 1.0)
+            .with(GoldenRatio, 0.5)
+            .with(Headroom, 0.75)
+            .with(LeadRoom, 0.75)
+    }
+
+    /// 人像：头顶留白和视线空间优先
+    pub fn portrait() -> Self {
+        Self::new()
+            .with(Headroom, 1.0)
+            .with(LeadRoom, 0.75)
+            .with(RuleOfThirds, 0.5)
+    }
+
+    /// 对称构图 (建筑、产品)
+    pub fn symmetric() -> Self {
+        Self::new().with(Centered, 1.0).with(Headroom, 0.25)
+    }
+
+    /// 动感构图：对角线和黄金分割
+    pub fn dynamic() -> Self {
+        Self::new()
+            .with(Diagonal, 1.0)
+            .with(GoldenRatio, 0.75)
+            .with(LeadRoom, 0.5)
+    }
+
+    /// 从名称解析预设组合 (`"balanced"`、`"portrait"`、`"symmetric"`、`"dynamic"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "balanced" => Some(Self::balanced()),
+            "portrait" => Some(Self::portrait()),
+            "symmetric" => Some(Self::symmetric()),
+            "dynamic" => Some(Self::dynamic()),
+            _ => None,
+        }
+    }
+
+    /// 规则名称和权重
+    pub fn rules(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.rules
+            .iter()
+            .map(|(rule, weight)| (rule.name(), *weight))
+    }
+}
+
+impl CompositionRule for WeightedComposition {
+    fn name(&self) -> &str {
+        "weighted"
+    }
+
+    fn score(&self, crop: &CropRect, subjects: &[Subject]) -> Option<f32> {
+        let (sum, total) = self
+            .rules
+            .iter()
+            .filter_map(|(rule, weight)| Some((rule.score(crop, subjects)?, *weight)))
+            .fold((0.0, 0.0), |(sum, total), (score, weight)| {
+                (sum + score * weight, total + weight)
+            });
+        (total > 0.0).then(|| sum / total)
+    }
+}
+
+impl fmt::Debug for WeightedComposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.rules()).finish()
+    }
+}
+
+/// 所有主体的外接矩形，没有主体时为 None
+pub(crate) fn subject_union(subjects: &[Subject]) -> Option<CropRect> {
+    if subjects.is_empty() {
+        return None;
+    }
+    let left = subjects.iter().map(|s| s.rect.x).fold(f32::MAX, f32::min);
+    let top = subjects.iter().map(|s| s.rect.y).fold(f32::MAX, f32::min);
+    let right = subjects
+        .iter()
+        .map(|s| s.rect.x + s.rect.width)
+        .fold(f32::MIN, f32::max);
+    let bottom = subjects
+        .iter()
+        .map(|s| s.rect.y + s.rect.height)
+        .fold(f32::MIN, f32::max);
+    Some(CropRect::new(left, top, right - left, bottom - top))
+}
+
+/// 矩形中心在裁剪区域中的相对位置 (0-1)
+fn relative_center(crop: &CropRect, rect: &CropRect) -> (f32, f32) {
+    (
+        (rect.x + rect.width / 2.0 - crop.x) / crop.width,
+        (rect.y + rect.height / 2.0 - crop.y) / crop.height,
+    )
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
@@ -0,0 +1,97 @@
+//! 带旋转角度的裁剪计算
+//!
+//! 旋转后的裁剪区域完全位于输入图像内，当且仅当它的外接矩形位于图像内，
+//! 因此所有约束都可以用外接矩形的半宽和半高表示。
+
+use super::ImageSize;
+use editor_studio_params::{CropRect, CropTransform};
+
+/// 旋转后的像素矩形的外接矩形半宽和半高
+fn half_extents(width: f32, height: f32, degrees: f32) -> (f32, f32) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+    (
+        (width * cos + height * sin) / 2.0,
+        (width * sin + height * cos) / 2.0,
+    )
+}
+
+/// 裁剪区域绕其中心允许的最大缩放倍数 (1.0 表示当前大小正好贴边)
+///
+/// 返回值小于 1.0 时说明当前裁剪区域在该角度下超出了图像。
+pub fn max_crop_scale(crop: &CropTransform, image: &ImageSize) -> f32 {
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let (ex, ey) = half_extents(
+        crop.rect.width * image_width,
+        crop.rect.height * image_height,
+        crop.angle,
+    );
+    let (cx, cy) = crop.center();
+    let room_x = (cx * image_width).min((1.0 - cx) * image_width).max(0.0);
+    let room_y = (cy * image_height).min((1.0 - cy) * image_height).max(0.0);
+
+    let scale_x = if ex > 0.0 { room_x / ex } else { f32::INFINITY };
+    let scale_y = if ey > 0.0 { room_y / ey } else { f32::INFINITY };
+    scale_x.min(scale_y)
+}
+
+/// 把旋转的裁剪区域限制在图像内，保持角度和像素宽高比
+///
+/// 旋转后放不进图像时先以中心等比缩小，再把中心移到允许的范围内。
+/// 裁剪区域或角度不是有限值时返回 None。
+pub fn constrain_crop(crop: &CropTransform, image: &ImageSize) -> Option<CropTransform> {
+    let rect = &crop.rect;
+    let values = [rect.x, rect.y, rect.width, rect.height, crop.angle];
+    if !values.iter().all(|v| v.is_finite()) {
+        return None;
+    }
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let mut width = crop.rect.width * image_width;
+    let mut height = crop.rect.height * image_height;
+
+    let (ex, ey) = half_extents(width, height, crop.angle);
+    let overflow = (2.0 * ex / image_width).max(2.0 * ey / image_height);
+    if overflow > 1.0 {
+        width /= overflow;
+        height /= overflow;
+    }
+
+    let (ex, ey) = half_extents(width, height, crop.angle);
+    let (cx, cy) = crop.center();
+    let cx = (cx * image_width).clamp(ex, (image_width - ex).max(ex));
+    let cy = (cy * image_height).clamp(ey, (image_height - ey).max(ey));
+
+    Some(CropTransform {
+        rect: CropRect {
+            x: (cx - width / 2.0) / image_width,
+            y: (cy - height / 2.0) / image_height,
+            width: width / image_width,
+            height: height / image_height,
+            ratio: crop.rect.ratio,
+        },
+        angle: crop.angle,
+    })
+}
+
+/// 图像中心处、指定角度下能放进图像的最大裁剪区域
+///
+/// # 参数
+/// * `ratio` - 像素宽高比 (宽 / 高)，None 表示保持图像的宽高比
+pub fn largest_crop(image: &ImageSize, angle: f32, ratio: Option<f32>) -> CropTransform {
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let pixel_ratio = ratio.unwrap_or(image.ratio());
+
+    // 高度为 1 像素时的外接矩形，按较紧的方向放大
+    let (ex, ey) = half_extents(pixel_ratio, 1.0, angle);
+    let height = (image_width / (2.0 * ex)).min(image_height / (2.0 * ey));
+    let width = height * pixel_ratio;
+
+    let rect = CropRect {
+        x: 0.5 - width / image_width / 2.0,
+        y: 0.5 - height / image_height / 2.0,
+        width: width / image_width,
+        height: height / image_height,
+        ratio,
+    };
+    CropTransform { rect, angle }
+}
@@ -0,0 +1,263 @@
+//! 几何变换计算
+//!
+//! 把有序的几何操作合成为一个从输出坐标到原图坐标的变换 (有透视校正时为单应性矩阵)，
+//! 这样整个操作栈只需要对原图重采样一次。
+
+use super::perspective::{keystone_corners, Homography};
+use super::ImageSize;
+use editor_studio_params::{CropRect, EditOperation, LayerTransform};
+
+/// 二维仿射变换
+///
+/// `(x, y) -> (a * x + b * y + c, d * x + e * y + f)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine2 {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Affine2 {
+    /// 恒等变换
+    pub const IDENTITY: Affine2 = Affine2 {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 0.0,
+        e: 1.0,
+        f: 0.0,
+    };
+
+    /// 平移
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self {
+            c: tx,
+            f: ty,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 缩放
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            e: sy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 旋转 (度，y 轴向下的图像坐标中为顺时针)
+    ///
+    /// 90 度的整数倍使用精确值，避免浮点误差破坏像素对齐。
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = sin_cos_degrees(degrees);
+        Self {
+            a: cos,
+            b: -sin,
+            d: sin,
+            e: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 组合变换：先应用 `other`，再应用 `self`
+    pub fn then_from(&self, other: &Affine2) -> Affine2 {
+        Affine2 {
+            a: self.a * other.a + self.b * other.d,
+            b: self.a * other.b + self.b * other.e,
+            c: self.a * other.c + self.b * other.f + self.c,
+            d: self.d * other.a + self.e * other.d,
+            e: self.d * other.b + self.e * other.e,
+            f: self.d * other.c + self.e * other.f + self.f,
+        }
+    }
+
+    /// 变换一个点
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.b * y + self.c,
+            self.d * x + self.e * y + self.f,
+        )
+    }
+
+    /// 逆变换 (不可逆时返回 None)
+    pub fn inverse(&self) -> Option<Affine2> {
+        let det = self.a * self.e - self.b * self.d;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Affine2 {
+            a: self.e * inv,
+            b: -self.b * inv,
+            c: (self.b * self.f - self.e * self.c) * inv,
+            d: -self.d * inv,
+            e: self.a * inv,
+            f: (self.d * self.c - self.a * self.f) * inv,
+        })
+    }
+
+    /// 转换为 3x3 齐次矩阵 (行优先)
+    pub fn to_mat3(&self) -> [[f32; 3]; 3] {
+        [
+            [self.a, self.b, self.c],
+            [self.d, self.e, self.f],
+            [0.0, 0.0, 1.0],
+        ]
+    }
+}
+
+/// 几何操作栈的求值结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeometryPlan {
+    /// 原图尺寸
+    pub source: ImageSize,
+    /// 输出尺寸
+    pub output: ImageSize,
+    /// 输出像素坐标 -> 原图像素坐标 (连续坐标，像素中心位于 +0.5)
+    pub to_source: Homography,
+}
+
+impl GeometryPlan {
+    /// 按顺序合成几何操作
+    pub fn new(source: &ImageSize, operations: &[EditOperation]) -> Self {
+        let mut to_source = Homography::IDENTITY;
+        let mut width = source.width as f32;
+        let mut height = source.height as f32;
+
+        for operation in operations {
+            // 当前操作的输出坐标 -> 输入坐标
+            let (inverse, out_width, out_height): (Homography, f32, f32) = match *operation {
+                EditOperation::Crop(rect) => crop_inverse(&rect, width, height),
+                EditOperation::StraightenCrop(crop) if crop.angle.rem_euclid(360.0) == 0.0 => {
+                    crop_inverse(&crop.rect, width, height)
+                }
+                EditOperation::StraightenCrop(crop) => {
+                    // 绕裁剪中心旋转并裁剪，合成一个变换
+                    let (cx, cy) = crop.center();
+                    let w = (crop.rect.width * width).round().max(1.0);
+                    let h = (crop.rect.height * height).round().max(1.0);
+                    let inverse = Affine2::translate(cx * width, cy * height)
+                        .then_from(&Affine2::rotate(-crop.angle))
+                        .then_from(&Affine2::translate(-w / 2.0, -h / 2.0));
+                    (inverse.into(), w, h)
+                }
+                EditOperation::Rotate { degrees } => {
+                    let (sin, cos) = sin_cos_degrees(degrees);
+                    let w = (width * cos.abs() + height * sin.abs()).round().max(1.0);
+                    let h = (width * sin.abs() + height * cos.abs()).round().max(1.0);
+                    let inverse = Affine2::translate(width / 2.0, height / 2.0)
+                        .then_from(&Affine2::rotate(-degrees))
+                        .then_from(&Affine2::translate(-w / 2.0, -h / 2.0));
+                    (inverse.into(), w, h)
+                }
+                EditOperation::Flip {
+                    horizontal,
+                    vertical,
+                } => {
+                    let (sx, tx) = if horizontal {
+                        (-1.0, width)
+                    } else {
+                        (1.0, 0.0)
+                    };
+                    let (sy, ty) = if vertical { (-1.0, height) } else { (1.0, 0.0) };
+                    let inverse = Affine2::translate(tx, ty).then_from(&Affine2::scale(sx, sy));
+                    (inverse.into(), width, height)
+                }
+                EditOperation::Perspective { corners } => {
+                    // 输出尺寸取四边形对边中较长的一条，避免丢失分辨率
+                    let points = corners.map(|[x, y]| (x * width, y * height));
+                    let length = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).hypot(a.1 - b.1);
+                    let w = length(points[0], points[1])
+                        .max(length(points[3], points[2]))
+                        .round()
+                        .max(1.0);
+                    let h = length(points[0], points[3])
+                        .max(length(points[1], points[2]))
+                        .round()
+                        .max(1.0);
+                    match Homography::from_rect(w, h, &points) {
+                        Some(inverse) => (inverse, w, h),
+                        None => (Homography::IDENTITY, width, height),
+                    }
+                }
+                EditOperation::Keystone {
+                    vertical,
+                    horizontal,
+                } => {
+                    let points = keystone_corners(vertical, horizontal)
+                        .map(|[x, y]| (x * width, y * height));
+                    let inverse = Homography::from_rect(width, height, &points)
+                        .unwrap_or(Homography::IDENTITY);
+                    (inverse, width, height)
+                }
+            };
+
+            to_source = to_source.then_from(&inverse);
+            width = out_width;
+            height = out_height;
+        }
+
+        Self {
+            source: *source,
+            output: ImageSize::new(width as u32, height as u32),
+            to_source,
+        }
+    }
+
+    /// 输出 UV (0-1) -> 原图 UV (0-1)
+    pub fn to_source_uv(&self) -> Homography {
+        Homography::from(Affine2::scale(
+            1.0 / self.source.width as f32,
+            1.0 / self.source.height as f32,
+        ))
+        .then_from(&self.to_source)
+        .then_from(&Affine2::scale(self.output.width as f32, self.output.height as f32).into())
+    }
+}
+
+/// 轴对齐裁剪：对齐到整数像素，纯裁剪不需要插值
+fn crop_inverse(rect: &CropRect, width: f32, height: f32) -> (Homography, f32, f32) {
+    // 未经验证的裁剪 (如配方中的) 可能越界或为 NaN，至少保留一个像素；
+    // `max`/`min` 遇到 NaN 时返回另一个参数，不会像 `clamp` 一样 panic
+    let x = (rect.x * width).round().max(0.0).min(width - 1.0);
+    let y = (rect.y * height).round().max(0.0).min(height - 1.0);
+    let w = (rect.width * width).round().max(1.0).min(width - x);
+    let h = (rect.height * height).round().max(1.0).min(height - y);
+    (Affine2::translate(x, y).into(), w, h)
+}
+
+/// 图层的放置：输出像素坐标 -> 图层 UV (0-1)，UV 在 0-1 之外的像素不被图层覆盖
+///
+/// # 参数
+/// * `output` - 输出图像尺寸
+/// * `layer` - 图层图像尺寸
+pub fn layer_to_uv(transform: &LayerTransform, output: &ImageSize, layer: &ImageSize) -> Affine2 {
+    let width = transform.scale * output.width as f32;
+    let height = width * layer.height as f32 / layer.width.max(1) as f32;
+    Affine2::translate(0.5, 0.5)
+        .then_from(&Affine2::scale(1.0 / width, 1.0 / height))
+        .then_from(&Affine2::rotate(-transform.rotation))
+        .then_from(&Affine2::translate(
+            -transform.x * output.width as f32,
+            -transform.y * output.height as f32,
+        ))
+}
+
+/// 角度的正弦和余弦，90 度的整数倍返回精确值
+fn sin_cos_degrees(degrees: f32) -> (f32, f32) {
+    let normalized = degrees.rem_euclid(360.0);
+    if normalized % 90.0 == 0.0 {
+        match normalized as u32 {
+            0 => (0.0, 1.0),
+            90 => (1.0, 0.0),
+            180 => (0.0, -1.0),
+            _ => (-1.0, 0.0),
+        }
+    } else {
+        normalized.to_radians().sin_cos()
+    }
+}
@@ -0,0 +1,538 @@
+//! 裁剪框手柄求解
+//!
+//! 拖动裁剪框的角、边或整个框时，计算新的有效裁剪区域。所有计算在像素空间中进行，
+//! 锁定的宽高比是像素宽高比。拖动角或边时固定对面的角或边 (从中心缩放时固定中心)，
+//! 结果总是满足最小尺寸并位于图像内；两者冲突时以图像边界为准。
+
+use super::ImageSize;
+use editor_studio_params::CropRect;
+
+/// 裁剪框手柄
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CropHandle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+    /// 拖动整个裁剪框
+    Move,
+}
+
+impl CropHandle {
+    /// 从名称解析 (如 `"top-left"`、`"move"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left" => Some(CropHandle::TopLeft),
+            "top" => Some(CropHandle::Top),
+            "top-right" => Some(CropHandle::TopRight),
+            "right" => Some(CropHandle::Right),
+            "bottom-right" => Some(CropHandle::BottomRight),
+            "bottom" => Some(CropHandle::Bottom),
+            "bottom-left" => Some(CropHandle::BottomLeft),
+            "left" => Some(CropHandle::Left),
+            "move" => Some(CropHandle::Move),
+            _ => None,
+        }
+    }
+
+    /// 手柄所在的边：-1 左/上，1 右/下，0 不在该方向上
+    fn sides(&self) -> (i8, i8) {
+        match self {
+            CropHandle::TopLeft => (-1, -1),
+            CropHandle::Top => (0, -1),
+            CropHandle::TopRight => (1, -1),
+            CropHandle::Right => (1, 0),
+            CropHandle::BottomRight => (1, 1),
+            CropHandle::Bottom => (0, 1),
+            CropHandle::BottomLeft => (-1, 1),
+            CropHandle::Left => (-1, 0),
+            CropHandle::Move => (0, 0),
+        }
+    }
+}
+
+/// 拖动手柄时的约束
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CropDragOptions {
+    /// 锁定的像素宽高比 (宽 / 高)
+    pub ratio: Option<f32>,
+    /// 裁剪框的最小边长 (像素)
+    pub min_size: f32,
+    /// 以裁剪框中心为固定点缩放 (Lightroom 中按住 Alt)
+    pub from_center: bool,
+    /// 拖动角时允许横竖方向互换 (如 3:2 变为 2:3)
+    pub swap_orientation: bool,
+}
+
+impl CropDragOptions {
+    pub const fn default() -> Self {
+        Self {
+            ratio: None,
+            min_size: 16.0,
+            from_center: false,
+            swap_orientation: true,
+        }
+    }
+}
+
+impl Default for CropDragOptions {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// 求解拖动手柄后的裁剪区域
+///
+/// # 参数
+/// * `crop` - 拖动开始时的裁剪区域 (归一化坐标)
+/// * `dx`, `dy` - 从拖动开始累计的位移 (归一化坐标)
+/// * `image` - 图像尺寸
+///
+/// 裁剪区域或位移不是有限值时返回 None。
+pub fn solve_crop_drag(
+    crop: &CropRect,
+    handle: CropHandle,
+    dx: f32,
+    dy: f32,
+    options: &CropDragOptions,
+    image: &ImageSize,
+) -> Option<CropRect> {
+    let values = [crop.x, crop.y, crop.width, crop.height, dx, dy];
+    if !values.iter().all(|v| v.is_finite()) {
+        return None;
+    }
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let left = crop.x * image_width;
+    let top = crop.y * image_height;
+    let width = crop.width * image_width;
+    let height = crop.height * image_height;
+    let (dx, dy) = (dx * image_width, dy * image_height);
+
+    if handle == CropHandle::Move {
+        let width = width.min(image_width);
+        let height = height.min(image_height);
+        let x = (left + dx).clamp(0.0, image_width - width);
+        let y = (top + dy).clamp(0.0, image_height - height);
+        return Some(normalize(x, y, width, height, crop.ratio, image));
+    }
+
+    let (side_x, side_y) = handle.sides();
+    let center = (left + width / 2.0, top + height / 2.0);
+
+    // 每个方向上的固定点和延伸方向 (-1 向左/上，1 向右/下，0 以固定点为中心)
+    let axis = |side: i8, start: f32, size: f32, center: f32, delta: f32| -> (f32, f32, f32) {
+        match (side, options.from_center) {
+            (0, _) => (center, 0.0, size),
+            (_, true) => (center, 0.0, size + 2.0 * delta * side as f32),
+            (-1, false) => (start + size, -1.0, size - delta),
+            _ => (start, 1.0, size + delta),
+        }
+    };
+    let (anchor_x, grow_x, mut new_width) = axis(side_x, left, width, center.0, dx);
+    let (anchor_y, grow_y, mut new_height) = axis(side_y, top, height, center.1, dy);
+
+    let mut ratio = options.ratio.filter(|r| *r > 0.0);
+    if let Some(r) = ratio {
+        let corner = side_x != 0 && side_y != 0;
+        // 拖动方向与宽高比的横竖方向相反时互换
+        if corner && options.swap_orientation && (r - 1.0).abs() > f32::EPSILON {
+            let wants_landscape = new_width.abs() > new_height.abs();
+            if wants_landscape != (r > 1.0) {
+                ratio = Some(1.0 / r);
+            }
+        }
+    }
+
+    new_width = new_width.max(0.0);
+    new_height = new_height.max(0.0);
+    if let Some(r) = ratio {
+        if side_y == 0 {
+            new_height = new_width / r;
+        } else if side_x == 0 {
+            new_width = new_height * r;
+        } else if new_width / r > new_height {
+            // 拖动角时取能覆盖指针位置的较大矩形
+            new_height = new_width / r;
+        } else {
+            new_width = new_height * r;
+        }
+    }
+
+    // 最小尺寸
+    let min_size = options.min_size.max(1.0);
+    if ratio.is_some() {
+        let grow =
+            (min_size / new_width.max(f32::EPSILON)).max(min_size / new_height.max(f32::EPSILON));
+        if grow > 1.0 {
+            new_width *= grow;
+            new_height *= grow;
+        }
+    } else {
+        new_width = new_width.max(min_size);
+        new_height = new_height.max(min_size);
+    }
+
+    // 图像边界：固定点不动，在延伸方向上能容纳的最大尺寸
+    let room = |anchor: f32, grow: f32, extent: f32| -> f32 {
+        match grow {
+            g if g > 0.0 => extent - anchor,
+            g if g < 0.0 => anchor,
+            _ => 2.0 * anchor.min(extent - anchor),
+        }
+    };
+    let max_width = room(anchor_x, grow_x, image_width).max(1.0);
+    let max_height = room(anchor_y, grow_y, image_height).max(1.0);
+    if ratio.is_some() {
+        let shrink = (max_width / new_width).min(max_height / new_height);
+        if shrink < 1.0 {
+            new_width *= shrink;
+            new_height *= shrink;
+        }
+    } else {
+        new_width = new_width.min(max_width);
+        new_height = new_height.min(max_height);
+    }
+
+    let place = |anchor: f32, grow: f32, size: f32| -> f32 {
+        match grow {
+            g if g > 0.0 => anchor,
+            g if g < 0.0 => anchor - size,
+            _ => anchor - size / 2.0,
+        }
+    };
+    let x = place(anchor_x, grow_x, new_width);
+    let y = place(anchor_y, grow_y, new_height);
+    Some(normalize(x, y, new_width, new_height, ratio, image))
+}
+
+/// 互换锁定宽高比的横竖方向 (如 3:2 变为 2:3)，保持中心和面积，并限制在图像内
+///
+/// # 参数
+/// * `ratio` - 当前锁定的像素宽高比，None 时使用裁剪区域本身的像素宽高比
+pub fn swap_crop_orientation(crop: &CropRect, ratio: Option<f32>, image: &ImageSize) -> CropRect {
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let width = crop.width * image_width;
+    let height = crop.height * image_height;
+    let ratio = ratio.unwrap_or(width / height);
+    let swapped = 1.0 / ratio;
+
+    let area = width * height;
+    let mut new_width = (area * swapped).sqrt();
+    let mut new_height = (area / swapped).sqrt();
+    let shrink = (image_width / new_width).min(image_height / new_height);
+    if shrink < 1.0 {
+        new_width *= shrink;
+        new_height *= shrink;
+    }
+
+    let center_x = crop.x * image_width + width / 2.0;
+    let center_y = crop.y * image_height + height / 2.0;
+    let x = (center_x - new_width / 2.0).clamp(0.0, (image_width - new_width).max(0.0));
+    let y = (center_y - new_height / 2.0).clamp(0.0, (image_height - new_height).max(0.0));
+    normalize(x, y, new_width, new_height, Some(swapped), image)
+}
+
+/// 像素矩形转换为归一化裁剪区域
+fn normalize(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    ratio: Option<f32>,
+    image: &ImageSize,
+) -> CropRect {
+    let (image_width, image_height) = (image.width as f32, image.height as f32);
+    let width = (width / image_width).min(1.0);
+    let height = (height / image_height).min(1.0);
+    CropRect {
+        x: (x / image_width).clamp(0.0, 1.0 - width),
+        y: (y / image_height).clamp(0.0, 1.0 - height),
+        width,
+        height,
+        ratio,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: ImageSize = ImageSize {
+        width: 1000,
+        height: 500,
+    };
+
+    /// 中间的 400x200 像素裁剪框
+    fn crop() -> CropRect {
+        CropRect::new(0.3, 0.3, 0.4, 0.4)
+    }
+
+    fn free() -> CropDragOptions {
+        CropDragOptions {
+            min_size: 10.0,
+            ..CropDragOptions::default()
+        }
+    }
+
+    fn locked(ratio: f32) -> CropDragOptions {
+        CropDragOptions {
+            ratio: Some(ratio),
+            ..free()
+        }
+    }
+
+    /// 裁剪区域的像素矩形 (左, 上, 宽, 高)
+    fn pixels(rect: &CropRect) -> [f32; 4] {
+        let (w, h) = (IMAGE.width as f32, IMAGE.height as f32);
+        [rect.x * w, rect.y * h, rect.width * w, rect.height * h]
+    }
+
+    fn assert_pixels(rect: &CropRect, expected: [f32; 4]) {
+        let actual = pixels(rect);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() < 0.01,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    fn assert_inside(rect: &CropRect) {
+        assert!(rect.x >= 0.0 && rect.y >= 0.0, "{:?}", rect);
+        assert!(rect.x + rect.width <= 1.0 + 1e-5, "{:?}", rect);
+        assert!(rect.y + rect.height <= 1.0 + 1e-5, "{:?}", rect);
+    }
+
+    #[test]
+    fn top_left_moves_corner_and_keeps_opposite() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::TopLeft, -0.1, -0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [200.0, 100.0, 500.0, 250.0]);
+    }
+
+    #[test]
+    fn top_moves_only_top_edge() {
+        let rect = solve_crop_drag(&crop(), CropHandle::Top, 0.2, 0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 200.0, 400.0, 150.0]);
+    }
+
+    #[test]
+    fn top_right_moves_corner_and_keeps_opposite() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::TopRight, 0.1, 0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 200.0, 500.0, 150.0]);
+    }
+
+    #[test]
+    fn right_moves_only_right_edge() {
+        let rect = solve_crop_drag(&crop(), CropHandle::Right, -0.1, 0.3, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 300.0, 200.0]);
+    }
+
+    #[test]
+    fn bottom_right_moves_corner_and_keeps_opposite() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::BottomRight, 0.1, 0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 500.0, 250.0]);
+    }
+
+    #[test]
+    fn bottom_moves_only_bottom_edge() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::Bottom, 0.1, -0.2, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 400.0, 100.0]);
+    }
+
+    #[test]
+    fn bottom_left_moves_corner_and_keeps_opposite() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::BottomLeft, 0.1, 0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [400.0, 150.0, 300.0, 250.0]);
+    }
+
+    #[test]
+    fn left_moves_only_left_edge() {
+        let rect = solve_crop_drag(&crop(), CropHandle::Left, -0.2, 0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [100.0, 150.0, 600.0, 200.0]);
+    }
+
+    #[test]
+    fn move_translates_and_stops_at_edges() {
+        let rect = solve_crop_drag(&crop(), CropHandle::Move, 0.1, -0.1, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [400.0, 100.0, 400.0, 200.0]);
+
+        let rect = solve_crop_drag(&crop(), CropHandle::Move, 0.5, 0.5, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [600.0, 300.0, 400.0, 200.0]);
+    }
+
+    #[test]
+    fn non_finite_input_is_rejected() {
+        for handle in [CropHandle::Move, CropHandle::TopLeft, CropHandle::Right] {
+            assert!(solve_crop_drag(&crop(), handle, f32::NAN, 0.0, &free(), &IMAGE).is_none());
+            assert!(
+                solve_crop_drag(&crop(), handle, 0.0, f32::INFINITY, &free(), &IMAGE).is_none()
+            );
+        }
+        let invalid = CropRect::new(f32::NAN, 0.1, 0.5, 0.5);
+        assert!(solve_crop_drag(&invalid, CropHandle::Move, 0.1, 0.1, &free(), &IMAGE).is_none());
+    }
+
+    #[test]
+    fn handles_are_clamped_to_image_bounds() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::TopLeft, -1.0, -1.0, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [0.0, 0.0, 700.0, 350.0]);
+
+        let rect = solve_crop_drag(&crop(), CropHandle::Right, 1.0, 0.0, &free(), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 700.0, 200.0]);
+    }
+
+    #[test]
+    fn handles_respect_minimum_size() {
+        let options = CropDragOptions {
+            min_size: 50.0,
+            ..free()
+        };
+        let rect = solve_crop_drag(
+            &crop(),
+            CropHandle::BottomRight,
+            -1.0,
+            -1.0,
+            &options,
+            &IMAGE,
+        )
+        .unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 50.0, 50.0]);
+
+        let rect = solve_crop_drag(&crop(), CropHandle::Left, 1.0, 0.0, &options, &IMAGE).unwrap();
+        assert_pixels(&rect, [650.0, 150.0, 50.0, 200.0]);
+    }
+
+    #[test]
+    fn locked_ratio_corner_covers_the_pointer() {
+        let rect = solve_crop_drag(
+            &crop(),
+            CropHandle::BottomRight,
+            0.2,
+            0.0,
+            &locked(2.0),
+            &IMAGE,
+        )
+        .unwrap();
+        assert_pixels(&rect, [300.0, 150.0, 600.0, 300.0]);
+        assert_eq!(rect.ratio, Some(2.0));
+    }
+
+    #[test]
+    fn locked_ratio_edge_resizes_around_the_edge_center() {
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::Right, 0.1, 0.0, &locked(2.0), &IMAGE).unwrap();
+        assert_pixels(&rect, [300.0, 125.0, 500.0, 250.0]);
+
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::Top, 0.0, -0.1, &locked(2.0), &IMAGE).unwrap();
+        assert_pixels(&rect, [250.0, 100.0, 500.0, 250.0]);
+    }
+
+    #[test]
+    fn locked_ratio_is_kept_when_clamped_to_bounds() {
+        let rect = solve_crop_drag(
+            &crop(),
+            CropHandle::BottomRight,
+            1.0,
+            1.0,
+            &locked(2.0),
+            &IMAGE,
+        )
+        .unwrap();
+        assert_inside(&rect);
+        let [x, y, w, h] = pixels(&rect);
+        assert!((w / h - 2.0).abs() < 1e-3);
+        assert!((x - 300.0).abs() < 0.01 && (y - 150.0).abs() < 0.01);
+        assert!((y + h - 500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn resize_from_center_keeps_center() {
+        let options = CropDragOptions {
+            from_center: true,
+            ..free()
+        };
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::BottomRight, 0.1, 0.1, &options, &IMAGE).unwrap();
+        assert_pixels(&rect, [200.0, 100.0, 600.0, 300.0]);
+
+        let rect = solve_crop_drag(&crop(), CropHandle::Left, 0.1, 0.0, &options, &IMAGE).unwrap();
+        assert_pixels(&rect, [400.0, 150.0, 200.0, 200.0]);
+    }
+
+    #[test]
+    fn resize_from_center_is_limited_by_the_nearest_edge() {
+        let options = CropDragOptions {
+            from_center: true,
+            ..locked(2.0)
+        };
+        let rect =
+            solve_crop_drag(&crop(), CropHandle::TopRight, 1.0, -1.0, &options, &IMAGE).unwrap();
+        assert_inside(&rect);
+        assert_pixels(&rect, [0.0, 0.0, 1000.0, 500.0]);
+    }
+
+    #[test]
+    fn corner_drag_swaps_ratio_orientation() {
+        let rect = solve_crop_drag(
+            &crop(),
+            CropHandle::BottomRight,
+            -0.3,
+            0.4,
+            &locked(2.0),
+            &IMAGE,
+        )
+        .unwrap();
+        assert_eq!(rect.ratio, Some(0.5));
+        let [_, _, w, h] = pixels(&rect);
+        assert!((w / h - 0.5).abs() < 1e-3);
+        assert_inside(&rect);
+
+        let options = CropDragOptions {
+            swap_orientation: false,
+            ..locked(2.0)
+        };
+        let rect = solve_crop_drag(
+            &crop(),
+            CropHandle::BottomRight,
+            -0.3,
+            0.4,
+            &options,
+            &IMAGE,
+        )
+        .unwrap();
+        assert_eq!(rect.ratio, Some(2.0));
+    }
+
+    #[test]
+    fn swap_orientation_keeps_center_and_area() {
+        let rect = swap_crop_orientation(&crop(), Some(2.0), &IMAGE);
+        assert_eq!(rect.ratio, Some(0.5));
+        let [x, y, w, h] = pixels(&rect);
+        assert!((w * h - 400.0 * 200.0).abs() < 1.0);
+        assert!((x + w / 2.0 - 500.0).abs() < 0.01);
+        assert!((y + h / 2.0 - 250.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn swap_orientation_shrinks_to_fit() {
+        let wide = CropRect::new(0.0, 0.2, 1.0, 0.6);
+        let rect = swap_crop_orientation(&wide, None, &IMAGE);
+        assert_inside(&rect);
+        let [_, _, w, h] = pixels(&rect);
+        assert!((h - 500.0).abs() < 0.01);
+        assert!((w / h - 300.0 / 1000.0).abs() < 1e-3);
+    }
+}
@@ -1,40 +1,64 @@
 //! 数学工具函数
 
+pub mod brush;
+pub mod composition;
+pub mod crop;
+pub mod geometry;
+pub mod handle;
+pub mod perspective;
+pub mod suggest;
+
+pub use brush::MaskRaster;
+pub use composition::{
+    Centered, CompositionRule, Diagonal, GoldenRatio, Headroom, LeadRoom, RuleOfThirds, Subject,
+    SubjectKind, WeightedComposition,
+};
+pub use crop::{constrain_crop, largest_crop, max_crop_scale};
+pub use geometry::{layer_to_uv, Affine2, GeometryPlan};
+pub use handle::{solve_crop_drag, swap_crop_orientation, CropDragOptions, CropHandle};
+pub use perspective::{keystone_corners, Homography};
+pub use suggest::{CropScorer, CropSuggester, CropSuggestion, RatioSuggestions, ScoreBreakdown};
+
 use editor_studio_params::CropRect;
 
 /// 计算包含边界框的裁剪区域，保持指定宽高比
+///
+/// 宽高比按像素计算：在 3:2 的图像上请求 1:1 得到的是像素意义上的正方形。
+/// 边界框放不下时，在图像内取该宽高比的最大区域，并尽量以边界框为中心。
+///
+/// # 参数
+/// * `bbox` - 归一化边界框 (0-1)
+/// * `target_ratio` - 像素宽高比 (宽 / 高)
+/// * `image` - 图像尺寸
 pub fn calculate_crop_containing(
     bbox: &CropRect,
     target_ratio: f32,
+    image: &ImageSize,
 ) -> CropRect {
+    // 归一化坐标中的宽高比
+    let ratio = image.normalized_ratio(target_ratio);
     let bbox_ratio = bbox.width / bbox.height;
 
-    let (width, height) = if bbox_ratio > target_ratio {
-        // 目标更窄，以高度为准
-        let height = bbox.height;
-        let width = height * target_ratio;
-        (width, height)
+    let (width, height) = if bbox_ratio > ratio {
+        // 边界框更宽，以宽度为准
+        (bbox.width, bbox.width / ratio)
     } else {
-        // 目标更宽，以宽度为准
-        let width = bbox.width;
-        let height = width / target_ratio;
-        (width, height)
+        // 边界框更高，以高度为准
+        (bbox.height * ratio, bbox.height)
     };
 
-    // 居中裁剪
-    let x = bbox.x + (bbox.width - width) / 2.0;
-    let y = bbox.y + (bbox.height - height) / 2.0;
-
-    CropRect {
-        x: x.max(0.0),
-        y: y.max(0.0),
+    // 以边界框为中心
+    let crop = CropRect {
+        x: bbox.x + (bbox.width - width) / 2.0,
+        y: bbox.y + (bbox.height - height) / 2.0,
         width,
         height,
         ratio: Some(target_ratio),
-    }
+    };
+    clamp_crop_inside(&crop)
 }
 
-/// 计算带填充的裁剪区域
+/// 计算带填充的裁剪区域，结果限制在图像内
 pub fn calculate_crop_with_padding(
     bbox: &CropRect,
     padding: f32, // 0.0 - 1.0
@@ -42,15 +66,42 @@ pub fn calculate_crop_with_padding(
     let padding_x = bbox.width * padding;
     let padding_y = bbox.height * padding;
 
+    let left = (bbox.x - padding_x).max(0.0);
+    let top = (bbox.y - padding_y).max(0.0);
+    let right = (bbox.x + bbox.width + padding_x).min(1.0);
+    let bottom = (bbox.y + bbox.height + padding_y).min(1.0);
+
     CropRect {
-        x: (bbox.x - padding_x).max(0.0),
-        y: (bbox.y - padding_y).max(0.0),
-        width: (bbox.width + padding_x * 2.0).min(1.0 - bbox.x),
-        height: (bbox.height + padding_y * 2.0).min(1.0 - bbox.y),
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
         ratio: None,
     }
 }
 
+/// 把裁剪区域限制在图像内，保持其宽高比
+///
+/// 超出图像的区域先以中心等比缩小到能放进图像，再平移到图像内。
+pub fn clamp_crop_inside(crop: &CropRect) -> CropRect {
+    let overflow = crop.width.max(crop.height);
+    let (width, height) = if overflow > 1.0 {
+        (crop.width / overflow, crop.height / overflow)
+    } else {
+        (crop.width, crop.height)
+    };
+    let center_x = crop.x + crop.width / 2.0;
+    let center_y = crop.y + crop.height / 2.0;
+
+    CropRect {
+        x: (center_x - width / 2.0).clamp(0.0, 1.0 - width),
+        y: (center_y - height / 2.0).clamp(0.0, 1.0 - height),
+        width,
+        height,
+        ratio: crop.ratio,
+    }
+}
+
 /// 应用三分法则评分
 pub fn rule_of_thirds_score(crop: &CropRect, subject: &CropRect) -> f32 {
     // 计算主体中心
@@ -85,6 +136,7 @@ pub fn rule_of_thirds_score(crop: &CropRect, subject: &CropRect) -> f32 {
 }
 
 /// 图像尺寸相关计算
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ImageSize {
     pub width: u32,
     pub height: u32,
@@ -99,6 +151,16 @@ impl ImageSize {
         self.width as f32 / self.height as f32
     }
 
+    /// 像素宽高比对应的归一化坐标宽高比
+    pub fn normalized_ratio(&self, pixel_ratio: f32) -> f32 {
+        pixel_ratio / self.ratio()
+    }
+
+    /// 归一化裁剪区域的像素宽高比
+    pub fn crop_ratio(&self, crop: &CropRect) -> f32 {
+        crop.width / crop.height * self.ratio()
+    }
+
     /// 计算适应容器的尺寸
     pub fn fit_in(&self, container: &ImageSize) -> ImageSize {
         let scale = (container.width as f32 / self.width as f32)
@@ -0,0 +1,168 @@
+//! 透视变换 (单应性矩阵)
+//!
+//! 四点透视校正和梯形校正都归结为一个单应性矩阵：把输出矩形的四个角
+//! 映射到输入图像中的四边形。仿射变换是其特例，几何操作栈合成时统一使用。
+
+use super::Affine2;
+
+/// 梯形校正滑块为 ±1 时，较短一边每侧收缩的比例
+const KEYSTONE_RANGE: f32 = 0.25;
+
+/// 单应性矩阵 (3x3，行优先)
+///
+/// `(x, y) -> ((a x + b y + c) / w, (d x + e y + f) / w)`，其中 `w = g x + h y + 1`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Homography {
+    pub m: [[f32; 3]; 3],
+}
+
+impl Homography {
+    /// 恒等变换
+    pub const IDENTITY: Homography = Homography {
+        m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    /// 从四组对应点估计单应性矩阵，`from[i]` 映射到 `to[i]`
+    ///
+    /// 任一组中有三点共线时无解，返回 None。
+    pub fn from_points(from: &[(f32, f32); 4], to: &[(f32, f32); 4]) -> Option<Self> {
+        // h33 = 1，每组对应点给出两个方程，共 8 个未知数
+        let mut system = [[0.0f64; 9]; 8];
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(to).enumerate() {
+            let (x, y, u, v) = (x as f64, y as f64, u as f64, v as f64);
+            system[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+            system[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+        }
+        let h = solve(system)?;
+        let homography = Self {
+            m: [
+                [h[0] as f32, h[1] as f32, h[2] as f32],
+                [h[3] as f32, h[4] as f32, h[5] as f32],
+                [h[6] as f32, h[7] as f32, 1.0],
+            ],
+        };
+        // 目标点共线时方程组仍可能有解，但得到的变换不可逆
+        homography.inverse().map(|_| homography)
+    }
+
+    /// 把矩形 `(0, 0)`-`(width, height)` 映射到四边形 (左上、右上、右下、左下)
+    pub fn from_rect(width: f32, height: f32, corners: &[(f32, f32); 4]) -> Option<Self> {
+        let rect = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)];
+        Self::from_points(&rect, corners)
+    }
+
+    /// 组合变换：先应用 `other`，再应用 `self`
+    pub fn then_from(&self, other: &Homography) -> Homography {
+        let (a, b) = (&self.m, &other.m);
+        Homography {
+            m: [0, 1, 2].map(|i| [0, 1, 2].map(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum())),
+        }
+    }
+
+    /// 变换一个点；落在地平线之后 (w <= 0) 的点返回无穷远
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let m = &self.m;
+        let w = m[2][0] * x + m[2][1] * y + m[2][2];
+        if w <= f32::EPSILON {
+            return (f32::INFINITY, f32::INFINITY);
+        }
+        (
+            (m[0][0] * x + m[0][1] * y + m[0][2]) / w,
+            (m[1][0] * x + m[1][1] * y + m[1][2]) / w,
+        )
+    }
+
+    /// 逆变换 (不可逆时返回 None)
+    pub fn inverse(&self) -> Option<Homography> {
+        let m = &self.m;
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let adjugate = [
+            [
+                cofactor(1, 2, 1, 2),
+                -cofactor(0, 2, 1, 2),
+                cofactor(0, 1, 1, 2),
+            ],
+            [
+                -cofactor(1, 2, 0, 2),
+                cofactor(0, 2, 0, 2),
+                -cofactor(0, 1, 0, 2),
+            ],
+            [
+                cofactor(1, 2, 0, 1),
+                -cofactor(0, 2, 0, 1),
+                cofactor(0, 1, 0, 1),
+            ],
+        ];
+        let det = m[0][0] * adjugate[0][0] + m[0][1] * adjugate[1][0] + m[0][2] * adjugate[2][0];
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Homography {
+            m: adjugate.map(|row| row.map(|value| value / det)),
+        })
+    }
+
+    /// 是否为仿射变换 (没有透视分量)
+    pub fn is_affine(&self) -> bool {
+        self.m[2][0] == 0.0 && self.m[2][1] == 0.0 && self.m[2][2] == 1.0
+    }
+
+    /// 3x3 齐次矩阵 (行优先)
+    pub fn to_mat3(&self) -> [[f32; 3]; 3] {
+        self.m
+    }
+}
+
+impl From<Affine2> for Homography {
+    fn from(affine: Affine2) -> Self {
+        Self {
+            m: affine.to_mat3(),
+        }
+    }
+}
+
+/// 梯形校正滑块对应的输入四边形 (左上、右上、右下、左下，归一化坐标)
+///
+/// 四边形总在输入图像内，校正后不会出现透明边缘。
+///
+/// # 参数
+/// * `vertical` - 竖直方向 (-1.0 到 1.0)，为正时收缩顶边，即拉宽输出的顶部
+/// * `horizontal` - 水平方向 (-1.0 到 1.0)，为正时收缩左边，即拉高输出的左侧
+pub fn keystone_corners(vertical: f32, horizontal: f32) -> [[f32; 2]; 4] {
+    let vertical = vertical.clamp(-1.0, 1.0) * KEYSTONE_RANGE;
+    let horizontal = horizontal.clamp(-1.0, 1.0) * KEYSTONE_RANGE;
+    let top = vertical.max(0.0);
+    let bottom = (-vertical).max(0.0);
+    let left = horizontal.max(0.0);
+    let right = (-horizontal).max(0.0);
+    [
+        [top, left],
+        [1.0 - top, right],
+        [1.0 - bottom, 1.0 - right],
+        [bottom, 1.0 - left],
+    ]
+}
+
+/// 高斯消元 (部分主元) 求解 8 元线性方程组，最后一列为常数项
+fn solve(mut system: [[f64; 9]; 8]) -> Option<[f64; 8]> {
+    for column in 0..8 {
+        let pivot = (column..8)
+            .max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))?;
+        if system[pivot][column].abs() < 1e-10 {
+            return None;
+        }
+        system.swap(column, pivot);
+        let pivot_row = system[column];
+        for (row, values) in system.iter_mut().enumerate() {
+            if row != column {
+                let factor = values[column] / pivot_row[column];
+                for (value, pivot) in values[column..].iter_mut().zip(&pivot_row[column..]) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+    }
+    Some(std::array::from_fn(|i| system[i][8] / system[i][i]))
+}
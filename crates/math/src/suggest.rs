@@ -0,0 +1,382 @@
+//! 裁剪建议
+//!
+//! 按主体边界框和目标宽高比生成候选裁剪 (不同的留白、缩放和主体位置)，
+//! 逐个评分后为每个宽高比返回得分最高的几个，供多裁剪预览条使用。
+//! 评分会避开切开人脸或其他主体的候选，并返回各项评分供界面解释建议。
+//! 所有坐标都是归一化坐标 (0-1)，宽高比按像素计算。
+
+use super::composition::{
+    subject_union, CompositionRule, Subject, SubjectKind, WeightedComposition,
+};
+use super::{calculate_crop_containing, calculate_crop_with_padding, clamp_crop_inside, ImageSize};
+use editor_studio_params::CropRect;
+
+/// 与已选建议重叠 (交并比) 超过此值的候选视为重复
+const DUPLICATE_OVERLAP: f32 = 0.85;
+/// 主体占裁剪区域面积的理想范围
+const FRAMING_RANGE: (f32, f32) = (0.15, 0.6);
+/// 主体露出 (或被裁掉) 的面积不超过此比例时不算切开
+const CUT_TOLERANCE: f32 = 0.01;
+/// 一般物体被切掉 (或只露出) 此比例的面积时切开惩罚达到最大
+const CUT_SEVERE: f32 = 0.25;
+/// 重要程度不低于最重要主体的此比例时为主要主体
+const PRIMARY_IMPORTANCE: f32 = 0.5;
+/// 主体在裁剪区域中的候选位置 (相对于裁剪区域的宽或高)
+const ANCHORS: [f32; 3] = [1.0 / 3.0, 0.5, 2.0 / 3.0];
+
+/// 一个裁剪建议
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CropSuggestion {
+    /// 裁剪区域 (`ratio` 为目标宽高比)
+    pub crop: CropRect,
+    /// 评分 (0.0 到 1.0)，即 `breakdown.total`
+    pub score: f32,
+    /// 各项评分，用于向用户解释这个建议
+    pub breakdown: ScoreBreakdown,
+}
+
+/// 一个宽高比的裁剪建议，按评分从高到低排列
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatioSuggestions {
+    /// 像素宽高比 (宽 / 高)
+    pub ratio: f32,
+    pub suggestions: Vec<CropSuggestion>,
+}
+
+/// 裁剪评分的各项 (均为 0.0 到 1.0)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScoreBreakdown {
+    /// 构图评分，没有适用的构图规则时为 None
+    pub composition: Option<f32>,
+    /// 完整保留的主体 (按重要程度加权)
+    pub containment: f32,
+    /// 主要主体在画面中的大小是否合适
+    pub framing: f32,
+    /// 保留的画面面积 (相对于该宽高比的最大裁剪)
+    pub area: f32,
+    /// 切开主体的惩罚，取被切开的主体中最严重的一个
+    pub cut_penalty: f32,
+    /// 被切开的主体数
+    pub cut_subjects: usize,
+    /// 总分：前四项的加权平均减去切开主体的惩罚
+    pub total: f32,
+}
+
+/// 裁剪评分：构图规则和各项的权重
+#[derive(Clone, Debug)]
+pub struct CropScorer {
+    /// 构图规则
+    pub rules: WeightedComposition,
+    /// 构图评分的权重
+    pub composition: f32,
+    /// 主体被完整保留的程度
+    pub containment: f32,
+    /// 主体在画面中的大小是否合适
+    pub framing: f32,
+    /// 保留的画面面积 (相对于该宽高比的最大裁剪)
+    pub area: f32,
+    /// 切开主体时从总分中扣除的最大分数
+    pub cutting: f32,
+}
+
+impl CropScorer {
+    /// 使用指定的构图规则
+    pub fn new(rules: WeightedComposition) -> Self {
+        Self {
+            rules,
+            composition: 0.3,
+            containment: 0.4,
+            framing: 0.2,
+            area: 0.1,
+            cutting: 0.5,
+        }
+    }
+
+    /// 为裁剪区域评分 (0.0 到 1.0)
+    ///
+    /// # 参数
+    /// * `subjects` - 主体，为空时把整张图像视为主体 (不评价构图)
+    pub fn score(&self, crop: &CropRect, subjects: &[Subject], image: &ImageSize) -> f32 {
+        self.breakdown(crop, subjects, image).total
+    }
+
+    /// 为裁剪区域评分并返回各项评分
+    ///
+    /// 完整保留的主体按重要程度计入 `containment`；部分落在裁剪区域内的主体
+    /// 视为被切开，按重要程度扣分，人脸无论切开多少都按最严重计。
+    /// 构图和主体大小只考虑主要主体 (重要程度不低于最重要主体的一半)，
+    /// 次要主体可以被完整排除在外。
+    pub fn breakdown(
+        &self,
+        crop: &CropRect,
+        subjects: &[Subject],
+        image: &ImageSize,
+    ) -> ScoreBreakdown {
+        let crop_area = area(crop);
+        if crop_area <= 0.0 {
+            return ScoreBreakdown::default();
+        }
+        let mut contained = 0.0;
+        let mut total_importance = 0.0;
+        let mut cut_penalty: f32 = 0.0;
+        let mut cut_subjects = 0;
+        for subject in subjects {
+            let subject_area = area(&subject.rect);
+            if subject_area <= 0.0 {
+                continue;
+            }
+            let visible = intersection_area(crop, &subject.rect) / subject_area;
+            total_importance += subject.importance;
+            if visible >= 1.0 - CUT_TOLERANCE {
+                contained += subject.importance;
+            } else if visible > CUT_TOLERANCE {
+                cut_subjects += 1;
+                cut_penalty = cut_penalty.max(subject.importance * cut_severity(subject, visible));
+            }
+        }
+        // 没有主体时按保留的图像面积计算，裁剪整张图像不算切开
+        let containment = if subjects.is_empty() {
+            intersection_area(crop, &subject_bounds(subjects))
+        } else if total_importance > 0.0 {
+            contained / total_importance
+        } else {
+            1.0
+        };
+
+        let primary = primary_subjects(subjects);
+        let framing = framing_score(area(&subject_bounds(&primary)) / crop_area);
+        let largest = largest_area(image.crop_ratio(crop), image);
+        let kept = (crop_area / largest).min(1.0);
+        let composition = self.rules.score(crop, &primary);
+
+        // 没有适用的构图规则时构图不参与加权
+        let composition_weight = composition.map_or(0.0, |_| self.composition);
+        let weight = composition_weight + self.containment + self.framing + self.area;
+        let total = if weight > 0.0 {
+            let mean = (composition_weight * composition.unwrap_or(0.0)
+                + self.containment * containment
+                + self.framing * framing
+                + self.area * kept)
+                / weight;
+            (mean - self.cutting * cut_penalty).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        ScoreBreakdown {
+            composition,
+            containment,
+            framing,
+            area: kept,
+            cut_penalty,
+            cut_subjects,
+            total,
+        }
+    }
+}
+
+impl Default for CropScorer {
+    fn default() -> Self {
+        Self::new(WeightedComposition::balanced())
+    }
+}
+
+/// 裁剪建议引擎
+#[derive(Clone, Debug)]
+pub struct CropSuggester {
+    /// 主体四周的留白 (相对于主体尺寸)
+    pub paddings: Vec<f32>,
+    /// 在包含主体的最小裁剪上再放大的倍数
+    pub scales: Vec<f32>,
+    /// 每个宽高比返回的建议数
+    pub count: usize,
+    pub scorer: CropScorer,
+}
+
+impl CropSuggester {
+    /// 每个宽高比返回 `count` 个建议
+    pub fn new(count: usize) -> Self {
+        Self {
+            paddings: vec![0.05, 0.15, 0.3, 0.5],
+            scales: vec![1.0, 1.25, 1.5, 2.0],
+            count,
+            scorer: CropScorer::default(),
+        }
+    }
+
+    /// 生成裁剪建议
+    ///
+    /// # 参数
+    /// * `image` - 图像尺寸
+    /// * `subjects` - 主体，为空时把整张图像视为主体
+    /// * `ratios` - 目标像素宽高比，每个宽高比返回一组建议 (顺序与输入一致)
+    pub fn suggest(
+        &self,
+        image: &ImageSize,
+        subjects: &[Subject],
+        ratios: &[f32],
+    ) -> Vec<RatioSuggestions> {
+        ratios
+            .iter()
+            .filter(|ratio| ratio.is_finite() && **ratio > 0.0)
+            .map(|&ratio| RatioSuggestions {
+                ratio,
+                suggestions: self.suggest_ratio(image, subjects, ratio),
+            })
+            .collect()
+    }
+
+    fn suggest_ratio(
+        &self,
+        image: &ImageSize,
+        subjects: &[Subject],
+        ratio: f32,
+    ) -> Vec<CropSuggestion> {
+        let mut candidates: Vec<CropSuggestion> = self
+            .candidates(image, subjects, ratio)
+            .into_iter()
+            .map(|crop| {
+                let breakdown = self.scorer.breakdown(&crop, subjects, image);
+                CropSuggestion {
+                    crop,
+                    score: breakdown.total,
+                    breakdown,
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        // 去掉与更高分的建议几乎相同的候选，让预览条有所区别；
+        // `count` 可能来自外部输入，预留的容量不超过候选数
+        let mut suggestions: Vec<CropSuggestion> =
+            Vec::with_capacity(self.count.min(candidates.len()));
+        for candidate in candidates {
+            if suggestions.len() >= self.count {
+                break;
+            }
+            let duplicate = suggestions
+                .iter()
+                .any(|chosen| overlap(&chosen.crop, &candidate.crop) > DUPLICATE_OVERLAP);
+            if !duplicate {
+                suggestions.push(candidate);
+            }
+        }
+        suggestions
+    }
+
+    /// 候选裁剪：留白 x 缩放 x 主体位置
+    ///
+    /// 分别围绕全部主体和主要主体生成，后者让次要主体有机会被完整排除在外。
+    fn candidates(&self, image: &ImageSize, subjects: &[Subject], ratio: f32) -> Vec<CropRect> {
+        let mut groups = vec![subject_bounds(subjects)];
+        let primary = subject_bounds(&primary_subjects(subjects));
+        if primary != groups[0] {
+            groups.push(primary);
+        }
+
+        let mut candidates = Vec::new();
+        for subject in groups {
+            let center_x = subject.x + subject.width / 2.0;
+            let center_y = subject.y + subject.height / 2.0;
+            for &padding in &self.paddings {
+                let padded = calculate_crop_with_padding(&subject, padding);
+                let tight = calculate_crop_containing(&padded, ratio, image);
+                for &scale in &self.scales {
+                    let width = tight.width * scale;
+                    let height = tight.height * scale;
+                    for anchor_x in ANCHORS {
+                        for anchor_y in ANCHORS {
+                            candidates.push(clamp_crop_inside(&CropRect {
+                                x: center_x - anchor_x * width,
+                                y: center_y - anchor_y * height,
+                                width,
+                                height,
+                                ratio: Some(ratio),
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        candidates
+    }
+}
+
+impl Default for CropSuggester {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// 所有主体的外接矩形，没有主体时为整张图像
+fn subject_bounds(subjects: &[Subject]) -> CropRect {
+    subject_union(subjects).unwrap_or_else(|| CropRect::new(0.0, 0.0, 1.0, 1.0))
+}
+
+/// 主要主体：重要程度不低于最重要主体的一半
+fn primary_subjects(subjects: &[Subject]) -> Vec<Subject> {
+    let most = subjects
+        .iter()
+        .map(|subject| subject.importance)
+        .fold(0.0, f32::max);
+    subjects
+        .iter()
+        .filter(|subject| subject.importance >= most * PRIMARY_IMPORTANCE)
+        .copied()
+        .collect()
+}
+
+/// 主体被切开的严重程度 (0.0 到 1.0)
+///
+/// 人脸只要被切开就是最严重；一般物体按露出和被裁掉两者中较小的一方计算，
+/// 边缘露出一小条与只裁掉一小角同样显眼。
+fn cut_severity(subject: &Subject, visible: f32) -> f32 {
+    match subject.kind {
+        SubjectKind::Face => 1.0,
+        SubjectKind::Object => (visible.min(1.0 - visible) / CUT_SEVERE).min(1.0),
+    }
+}
+
+fn area(rect: &CropRect) -> f32 {
+    rect.width.max(0.0) * rect.height.max(0.0)
+}
+
+/// 两个矩形相交部分的面积
+fn intersection_area(a: &CropRect, b: &CropRect) -> f32 {
+    let width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    width.max(0.0) * height.max(0.0)
+}
+
+/// 交并比
+fn overlap(a: &CropRect, b: &CropRect) -> f32 {
+    let intersection = intersection_area(a, b);
+    let union = area(a) + area(b) - intersection;
+    if union <= 0.0 {
+        0.0
+    } else {
+        intersection / union
+    }
+}
+
+/// 主体占裁剪面积的比例在理想范围内为 1，向两侧线性下降
+fn framing_score(fraction: f32) -> f32 {
+    let (low, high) = FRAMING_RANGE;
+    if fraction < low {
+        fraction / low
+    } else if fraction > high {
+        ((1.0 - fraction) / (1.0 - high)).max(0.0)
+    } else {
+        1.0
+    }
+}
+
+/// 该像素宽高比在图像内的最大裁剪面积 (归一化)
+fn largest_area(pixel_ratio: f32, image: &ImageSize) -> f32 {
+    let ratio = image.normalized_ratio(pixel_ratio);
+    if ratio >= 1.0 {
+        1.0 / ratio
+    } else {
+        ratio
+    }
+}
@@ -0,0 +1,147 @@
+//! 调整参数的复制与粘贴
+//!
+//! 复制的内容是完整的编辑状态加上原图像的宽高比，粘贴时按分组选择要应用的字段。
+
+use super::{EditRecipe, EditState};
+use serde_json::Value;
+
+/// 粘贴分组
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PasteGroups {
+    /// 基础影调 (亮度、对比度)
+    pub basic_tone: bool,
+    /// 色彩 (饱和度、色彩分级)
+    pub color: bool,
+    /// 细节 (锐化、降噪等)；管线中还没有这类参数，目前粘贴时不修改任何内容
+    pub detail: bool,
+    /// 裁剪
+    pub crop: bool,
+    /// 几何变换 (旋转、翻转)
+    pub geometry: bool,
+    /// 局部调整蒙版
+    pub masks: bool,
+}
+
+impl PasteGroups {
+    /// 全部分组
+    pub const ALL: PasteGroups = PasteGroups {
+        basic_tone: true,
+        color: true,
+        detail: true,
+        crop: true,
+        geometry: true,
+        masks: true,
+    };
+
+    /// 不粘贴任何分组
+    pub const NONE: PasteGroups = PasteGroups {
+        basic_tone: false,
+        color: false,
+        detail: false,
+        crop: false,
+        geometry: false,
+        masks: false,
+    };
+
+    /// 从分组名称列表解析
+    ///
+    /// 可用名称：`basic_tone`、`color`、`detail`、`crop`、`geometry`、`masks`
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<Self, String> {
+        let mut groups = Self::NONE;
+        for name in names {
+            match name.as_ref() {
+                "basic_tone" => groups.basic_tone = true,
+                "color" => groups.color = true,
+                "detail" => groups.detail = true,
+                "crop" => groups.crop = true,
+                "geometry" => groups.geometry = true,
+                "masks" => groups.masks = true,
+                other => return Err(format!("Unknown paste group: {}", other)),
+            }
+        }
+        Ok(groups)
+    }
+}
+
+impl Default for PasteGroups {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// 复制的调整参数
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdjustmentClipboard {
+    /// 复制时的编辑状态
+    pub state: EditState,
+    /// 原图像的像素宽高比 (宽 / 高)
+    pub source_aspect: f32,
+}
+
+impl AdjustmentClipboard {
+    /// 复制编辑状态
+    pub fn copy(state: &EditState, source_aspect: f32) -> Self {
+        Self {
+            state: state.clone(),
+            source_aspect,
+        }
+    }
+
+    /// 把选中的分组粘贴到目标编辑状态
+    ///
+    /// 裁剪区域会按目标图像的宽高比重新映射，保持像素宽高比不变。
+    pub fn paste(&self, target: &mut EditState, groups: PasteGroups, target_aspect: f32) {
+        let source = &self.state;
+        if groups.basic_tone {
+            target.adjustments.brightness = source.adjustments.brightness;
+            target.adjustments.contrast = source.adjustments.contrast;
+        }
+        if groups.color {
+            target.adjustments.saturation = source.adjustments.saturation;
+            target.grading = source.grading;
+        }
+        if groups.geometry {
+            let (horizontal, vertical) = source.flip();
+            target.set_rotation(source.rotation());
+            target.set_flip(horizontal, vertical);
+        }
+        if groups.masks {
+            // 蒙版使用归一化坐标，直接适用于任意尺寸的图像
+            target.masks = source.masks.clone();
+        }
+        if groups.crop {
+            target.set_crop(
+                source
+                    .crop()
+                    .map(|crop| crop.remap(self.source_aspect, target_aspect)),
+            );
+        }
+    }
+
+    /// 序列化为 JSON (配方格式，附加原图像宽高比)
+    pub fn to_json(&self) -> Result<String, String> {
+        let mut value = serde_json::to_value(EditRecipe::new(self.state.clone()))
+            .map_err(|e| format!("Failed to serialize clipboard: {}", e))?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert("source_aspect".to_string(), Value::from(self.source_aspect));
+        }
+        Ok(value.to_string())
+    }
+
+    /// 从 JSON 解析
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid clipboard JSON: {}", e))?;
+        let source_aspect = value
+            .get("source_aspect")
+            .and_then(Value::as_f64)
+            .filter(|aspect| *aspect > 0.0)
+            .ok_or_else(|| "Invalid clipboard: missing source_aspect".to_string())?
+            as f32;
+        let recipe = EditRecipe::from_value(value)?;
+        Ok(Self {
+            state: recipe.state,
+            source_aspect,
+        })
+    }
+}
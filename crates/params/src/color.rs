@@ -0,0 +1,116 @@
+//! 色彩空间定义
+
+/// RGB 色彩空间
+///
+/// 两者都使用 sRGB 传递函数和 D65 白点，只有原色不同，
+/// 因此 8 位数据都可以直接放进 `Rgba8UnormSrgb` 纹理中线性化。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// sRGB (IEC 61966-2-1)
+    #[default]
+    Srgb,
+    /// Display P3 (DCI-P3 原色，D65 白点)
+    DisplayP3,
+}
+
+/// D65 白点色度坐标
+const D65: (f32, f32) = (0.3127, 0.3290);
+
+impl ColorSpace {
+    /// 编辑时使用的工作色彩空间
+    pub const WORKING: ColorSpace = ColorSpace::DisplayP3;
+
+    /// 名称 (与 CSS `color()` 中的写法一致)
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::DisplayP3 => "display-p3",
+        }
+    }
+
+    /// 从名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "srgb" => Some(ColorSpace::Srgb),
+            "display-p3" | "p3" => Some(ColorSpace::DisplayP3),
+            _ => None,
+        }
+    }
+
+    /// 红、绿、蓝原色的 xy 色度坐标
+    pub fn primaries(&self) -> [(f32, f32); 3] {
+        match self {
+            ColorSpace::Srgb => [(0.640, 0.330), (0.300, 0.600), (0.150, 0.060)],
+            ColorSpace::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+        }
+    }
+
+    /// 白点 xy 色度坐标
+    pub fn white_point(&self) -> (f32, f32) {
+        D65
+    }
+
+    /// 线性 RGB 到 CIE XYZ (D65) 的矩阵
+    pub fn to_xyz(&self) -> [[f32; 3]; 3] {
+        let xyz = |(x, y): (f32, f32)| [x / y, 1.0, (1.0 - x - y) / y];
+        let [r, g, b] = self.primaries().map(xyz);
+        let primaries = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+
+        // 按白点缩放各原色，使 RGB (1, 1, 1) 映射到白点
+        let white = xyz(self.white_point());
+        let s = mat3_mul_vec(&mat3_inverse(&primaries), white);
+
+        let mut m = primaries;
+        for row in m.iter_mut() {
+            for (value, scale) in row.iter_mut().zip(s) {
+                *value *= scale;
+            }
+        }
+        m
+    }
+
+    /// 线性 RGB 从当前色彩空间转换到 `target` 的矩阵
+    pub fn conversion_matrix(&self, target: ColorSpace) -> [[f32; 3]; 3] {
+        if *self == target {
+            return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        }
+        mat3_mul(&mat3_inverse(&target.to_xyz()), &self.to_xyz())
+    }
+}
+
+fn mat3_mul(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+fn mat3_mul_vec(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn mat3_inverse(m: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let [[a, b, c], [d, e, f], [g, h, i]] = *m;
+    let det = a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+    let inv = 1.0 / det;
+    [
+        [
+            (e * i - f * h) * inv,
+            (c * h - b * i) * inv,
+            (b * f - c * e) * inv,
+        ],
+        [
+            (f * g - d * i) * inv,
+            (a * i - c * g) * inv,
+            (c * d - a * f) * inv,
+        ],
+        [
+            (d * h - e * g) * inv,
+            (b * g - a * h) * inv,
+            (a * e - b * d) * inv,
+        ],
+    ]
+}
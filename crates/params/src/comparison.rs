@@ -0,0 +1,105 @@
+//! 前后对比设置
+//!
+//! 对比只影响预览，不属于编辑状态：不会记录到历史，也不会写入配方。
+//! "之前" 指未应用调整参数的原图，但保留几何操作，使两侧画面对齐。
+
+/// 对比模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ComparisonMode {
+    /// 只显示编辑后的图像
+    #[default]
+    Off,
+    /// 可拖动的分割线，一侧为原图，另一侧为编辑后
+    Split,
+    /// 并排显示完整的原图和编辑后的图像
+    SideBySide,
+    /// 按住时显示原图
+    PressAndHold,
+}
+
+impl ComparisonMode {
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComparisonMode::Off => "off",
+            ComparisonMode::Split => "split",
+            ComparisonMode::SideBySide => "side-by-side",
+            ComparisonMode::PressAndHold => "press-and-hold",
+        }
+    }
+
+    /// 从名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(ComparisonMode::Off),
+            "split" => Some(ComparisonMode::Split),
+            "side-by-side" => Some(ComparisonMode::SideBySide),
+            "press-and-hold" => Some(ComparisonMode::PressAndHold),
+            _ => None,
+        }
+    }
+}
+
+/// 分割方向
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitOrientation {
+    /// 竖直分割线，原图在左
+    #[default]
+    Vertical,
+    /// 水平分割线，原图在上
+    Horizontal,
+}
+
+impl SplitOrientation {
+    /// 从名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "vertical" => Some(SplitOrientation::Vertical),
+            "horizontal" => Some(SplitOrientation::Horizontal),
+            _ => None,
+        }
+    }
+}
+
+/// 前后对比设置
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComparisonState {
+    /// 对比模式
+    pub mode: ComparisonMode,
+    /// 分割方向 (分割和并排模式)
+    pub orientation: SplitOrientation,
+    /// 分割线位置 (0.0 到 1.0)
+    pub position: f32,
+    /// 是否绘制分割线
+    pub show_divider: bool,
+    /// 是否正在按住 (按住对比模式)
+    pub holding: bool,
+}
+
+impl Default for ComparisonState {
+    fn default() -> Self {
+        ComparisonState::default()
+    }
+}
+
+impl ComparisonState {
+    pub const fn default() -> Self {
+        Self {
+            mode: ComparisonMode::Off,
+            orientation: SplitOrientation::Vertical,
+            position: 0.5,
+            show_divider: true,
+            holding: false,
+        }
+    }
+
+    /// 设置分割线位置
+    pub fn set_position(&mut self, position: f32) {
+        self.position = position.clamp(0.0, 1.0);
+    }
+
+    /// 当前是否整张显示原图
+    pub fn shows_original(&self) -> bool {
+        self.mode == ComparisonMode::PressAndHold && self.holding
+    }
+}
@@ -0,0 +1,180 @@
+//! 色彩分级参数定义
+
+use serde::{Deserialize, Serialize};
+
+/// 色轮 (色相/饱和度/明度偏移)
+///
+/// 布局与 WGSL 中的 `ColorWheel` 一致，占 16 字节。
+#[repr(C)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    bytemuck::Pod,
+    bytemuck::Zeroable,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct ColorWheel {
+    /// 色相 (0.0 到 360.0 度)
+    pub hue: f32,
+    /// 饱和度 (0.0 到 1.0)
+    pub saturation: f32,
+    /// 明度偏移 (-1.0 到 1.0)
+    pub luminance: f32,
+    /// 填充对齐
+    #[serde(skip)]
+    pub _padding: f32,
+}
+
+impl ColorWheel {
+    pub const fn default() -> Self {
+        Self {
+            hue: 0.0,
+            saturation: 0.0,
+            luminance: 0.0,
+            _padding: 0.0,
+        }
+    }
+
+    pub fn new(hue: f32, saturation: f32, luminance: f32) -> Self {
+        let mut wheel = Self::default();
+        wheel.set(hue, saturation, luminance);
+        wheel
+    }
+
+    /// 设置色相、饱和度和明度
+    pub fn set(&mut self, hue: f32, saturation: f32, luminance: f32) {
+        self.hue = hue.rem_euclid(360.0);
+        self.saturation = saturation.clamp(0.0, 1.0);
+        self.luminance = luminance.clamp(-1.0, 1.0);
+    }
+
+    /// 是否为无效果的色轮
+    pub fn is_neutral(&self) -> bool {
+        self.saturation == 0.0 && self.luminance == 0.0
+    }
+
+    /// 限制到有效范围
+    pub fn clamped(self) -> Self {
+        Self::new(self.hue, self.saturation, self.luminance)
+    }
+}
+
+/// 色调范围
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneRange {
+    /// 阴影
+    Shadows,
+    /// 中间调
+    Midtones,
+    /// 高光
+    Highlights,
+    /// 全局
+    Global,
+}
+
+/// 三向色彩分级参数
+///
+/// 作为独立的 uniform 块传给着色器，布局满足 WGSL 的 16 字节对齐规则：
+/// 四个色轮各占 16 字节，最后的 `blending`/`balance` 补齐到 80 字节。
+#[repr(C)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct ColorGrading {
+    /// 阴影色轮
+    pub shadows: ColorWheel,
+    /// 中间调色轮
+    pub midtones: ColorWheel,
+    /// 高光色轮
+    pub highlights: ColorWheel,
+    /// 全局色轮
+    pub global: ColorWheel,
+    /// 色调范围之间的混合程度 (0.0 到 1.0)
+    pub blending: f32,
+    /// 阴影/高光平衡 (-1.0 到 1.0)
+    pub balance: f32,
+    /// 填充对齐
+    #[serde(skip)]
+    pub _padding: [f32; 2],
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        ColorGrading::default()
+    }
+}
+
+impl ColorGrading {
+    pub const fn default() -> Self {
+        Self {
+            shadows: ColorWheel::default(),
+            midtones: ColorWheel::default(),
+            highlights: ColorWheel::default(),
+            global: ColorWheel::default(),
+            blending: 0.5,
+            balance: 0.0,
+            _padding: [0.0; 2],
+        }
+    }
+
+    /// 重置为默认值
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 获取指定范围的色轮
+    pub fn wheel(&self, range: ToneRange) -> &ColorWheel {
+        match range {
+            ToneRange::Shadows => &self.shadows,
+            ToneRange::Midtones => &self.midtones,
+            ToneRange::Highlights => &self.highlights,
+            ToneRange::Global => &self.global,
+        }
+    }
+
+    /// 设置指定范围的色轮
+    pub fn set_wheel(&mut self, range: ToneRange, hue: f32, saturation: f32, luminance: f32) {
+        let wheel = match range {
+            ToneRange::Shadows => &mut self.shadows,
+            ToneRange::Midtones => &mut self.midtones,
+            ToneRange::Highlights => &mut self.highlights,
+            ToneRange::Global => &mut self.global,
+        };
+        wheel.set(hue, saturation, luminance);
+    }
+
+    /// 设置混合程度
+    pub fn set_blending(&mut self, value: f32) {
+        self.blending = value.clamp(0.0, 1.0);
+    }
+
+    /// 设置平衡
+    pub fn set_balance(&mut self, value: f32) {
+        self.balance = value.clamp(-1.0, 1.0);
+    }
+
+    /// 限制到有效范围
+    pub fn clamped(mut self) -> Self {
+        self.shadows = self.shadows.clamped();
+        self.midtones = self.midtones.clamped();
+        self.highlights = self.highlights.clamped();
+        self.global = self.global.clamped();
+        self.set_blending(self.blending);
+        self.set_balance(self.balance);
+        self
+    }
+
+    /// 是否没有任何分级效果
+    pub fn is_neutral(&self) -> bool {
+        self.shadows.is_neutral()
+            && self.midtones.is_neutral()
+            && self.highlights.is_neutral()
+            && self.global.is_neutral()
+    }
+}
@@ -0,0 +1,275 @@
+//! 撤销/重做历史
+//!
+//! 每次编辑记录为一条命令，保存编辑前后的完整状态。连续拖动滑块时同一命令
+//! 的多次记录会合并为一步，直到调用 [`EditHistory::seal`] 结束这次拖动。
+//! 历史记录总内存超过预算时丢弃最早的步骤。
+
+use super::{EditState, ToneRange};
+use std::collections::VecDeque;
+
+/// 默认内存预算 (8 MB)
+pub const DEFAULT_MEMORY_BUDGET: usize = 8 * 1024 * 1024;
+
+/// 可调整的参数字段
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdjustField {
+    Brightness,
+    Contrast,
+    Saturation,
+    ColorWheel(ToneRange),
+    GradingBlending,
+    GradingBalance,
+}
+
+/// 编辑命令
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditCommand {
+    /// 修改调整参数
+    Adjust(AdjustField),
+    /// 修改裁剪区域
+    Crop,
+    /// 旋转
+    Rotate,
+    /// 翻转
+    Flip,
+    /// 透视校正 (四点或梯形校正)
+    Perspective,
+    /// 删除几何操作
+    RemoveOperation,
+    /// 调整几何操作顺序
+    MoveOperation,
+    /// 添加蒙版
+    AddMask,
+    /// 修改蒙版 (形状或参数)
+    EditMask(usize),
+    /// 删除蒙版
+    RemoveMask,
+    /// 画笔笔触 (每个笔触单独撤销)
+    BrushStroke,
+    /// 添加图层
+    AddLayer,
+    /// 修改图层 (位置、不透明度或混合模式)
+    EditLayer(usize),
+    /// 删除图层
+    RemoveLayer,
+    /// 调整图层顺序
+    MoveLayer,
+    /// 应用预设
+    ApplyPreset(String),
+    /// 粘贴调整参数
+    Paste,
+    /// 应用配方
+    ApplyRecipe,
+    /// 恢复快照
+    RestoreSnapshot(String),
+    /// 重置调整
+    Reset,
+}
+
+impl EditCommand {
+    /// 连续的同一命令是否合并为一步 (滑块拖动、裁剪框、透视角点、蒙版或图层拖动)
+    pub fn coalesces(&self) -> bool {
+        matches!(
+            self,
+            EditCommand::Adjust(_)
+                | EditCommand::Crop
+                | EditCommand::Perspective
+                | EditCommand::EditMask(_)
+                | EditCommand::EditLayer(_)
+        )
+    }
+
+    /// 命令名称，用于界面显示 (如 "Undo brightness")
+    pub fn label(&self) -> String {
+        match self {
+            EditCommand::Adjust(field) => match field {
+                AdjustField::Brightness => "brightness".to_string(),
+                AdjustField::Contrast => "contrast".to_string(),
+                AdjustField::Saturation => "saturation".to_string(),
+                AdjustField::ColorWheel(range) => format!("{:?} wheel", range).to_lowercase(),
+                AdjustField::GradingBlending => "grading blending".to_string(),
+                AdjustField::GradingBalance => "grading balance".to_string(),
+            },
+            EditCommand::Crop => "crop".to_string(),
+            EditCommand::Rotate => "rotate".to_string(),
+            EditCommand::Flip => "flip".to_string(),
+            EditCommand::Perspective => "perspective".to_string(),
+            EditCommand::RemoveOperation => "remove operation".to_string(),
+            EditCommand::MoveOperation => "reorder operations".to_string(),
+            EditCommand::AddMask => "add mask".to_string(),
+            EditCommand::EditMask(_) => "mask".to_string(),
+            EditCommand::RemoveMask => "remove mask".to_string(),
+            EditCommand::BrushStroke => "brush stroke".to_string(),
+            EditCommand::AddLayer => "add layer".to_string(),
+            EditCommand::EditLayer(_) => "layer".to_string(),
+            EditCommand::RemoveLayer => "remove layer".to_string(),
+            EditCommand::MoveLayer => "reorder layers".to_string(),
+            EditCommand::ApplyPreset(name) => format!("preset {}", name),
+            EditCommand::Paste => "paste adjustments".to_string(),
+            EditCommand::ApplyRecipe => "apply recipe".to_string(),
+            EditCommand::RestoreSnapshot(name) => format!("snapshot {}", name),
+            EditCommand::Reset => "reset".to_string(),
+        }
+    }
+}
+
+/// 历史记录中的一步
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    /// 命令
+    pub command: EditCommand,
+    /// 编辑前的状态
+    pub before: EditState,
+    /// 编辑后的状态
+    pub after: EditState,
+}
+
+impl HistoryEntry {
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.before.heap_size() + self.after.heap_size()
+    }
+}
+
+/// 撤销/重做历史
+#[derive(Clone, Debug)]
+pub struct EditHistory {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    memory_budget: usize,
+    memory_used: usize,
+    sealed: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self::with_budget(DEFAULT_MEMORY_BUDGET)
+    }
+
+    /// 创建指定内存预算 (字节) 的历史记录
+    pub fn with_budget(memory_budget: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            memory_budget,
+            memory_used: 0,
+            sealed: true,
+        }
+    }
+
+    /// 记录一次编辑，编辑前后状态相同时忽略
+    pub fn record(&mut self, command: EditCommand, before: EditState, after: &EditState) {
+        if before == *after {
+            return;
+        }
+
+        self.clear_redo();
+
+        // 与上一步合并
+        if !self.sealed && command.coalesces() {
+            if let Some(last) = self.undo_stack.back_mut() {
+                if last.command == command {
+                    self.memory_used -= last.memory_size();
+                    last.after = after.clone();
+                    self.memory_used += last.memory_size();
+                    self.enforce_budget();
+                    return;
+                }
+            }
+        }
+
+        let entry = HistoryEntry {
+            command,
+            before,
+            after: after.clone(),
+        };
+        self.sealed = !entry.command.coalesces();
+        self.memory_used += entry.memory_size();
+        self.undo_stack.push_back(entry);
+        self.enforce_budget();
+    }
+
+    /// 结束当前的连续编辑 (如滑块拖动结束)，之后的编辑不再合并到上一步
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// 撤销一步，返回撤销的命令
+    pub fn undo(&mut self, state: &mut EditState) -> Option<EditCommand> {
+        let entry = self.undo_stack.pop_back()?;
+        *state = entry.before.clone();
+        let command = entry.command.clone();
+        self.redo_stack.push(entry);
+        self.sealed = true;
+        Some(command)
+    }
+
+    /// 重做一步，返回重做的命令
+    pub fn redo(&mut self, state: &mut EditState) -> Option<EditCommand> {
+        let entry = self.redo_stack.pop()?;
+        *state = entry.after.clone();
+        let command = entry.command.clone();
+        self.undo_stack.push_back(entry);
+        self.sealed = true;
+        Some(command)
+    }
+
+    /// 是否可以撤销
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// 是否可以重做
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// 下一步撤销的命令
+    pub fn undo_command(&self) -> Option<&EditCommand> {
+        self.undo_stack.back().map(|entry| &entry.command)
+    }
+
+    /// 下一步重做的命令
+    pub fn redo_command(&self) -> Option<&EditCommand> {
+        self.redo_stack.last().map(|entry| &entry.command)
+    }
+
+    /// 清空历史记录
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.memory_used = 0;
+        self.sealed = true;
+    }
+
+    /// 设置内存预算 (字节)
+    pub fn set_memory_budget(&mut self, memory_budget: usize) {
+        self.memory_budget = memory_budget;
+        self.enforce_budget();
+    }
+
+    /// 当前占用的内存 (字节)
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
+    fn clear_redo(&mut self) {
+        for entry in self.redo_stack.drain(..) {
+            self.memory_used -= entry.memory_size();
+        }
+    }
+
+    /// 丢弃最早的步骤直到不超过预算 (至少保留最近一步)
+    fn enforce_budget(&mut self) {
+        while self.memory_used > self.memory_budget && self.undo_stack.len() > 1 {
+            if let Some(entry) = self.undo_stack.pop_front() {
+                self.memory_used -= entry.memory_size();
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! 图层与混合模式
+//!
+//! 图层把另一张图像 (标志、纹理、第二张照片) 叠加到编辑后的图像上。
+//! 图层只引用图像编号，像素数据由调用方保存，编辑状态仍然不包含像素。
+//! 混合在线性工作色彩空间中逐通道进行，导出时由 CPU 合成。
+
+use serde::{Deserialize, Serialize};
+
+/// 图层数量上限
+pub const MAX_LAYERS: usize = 8;
+
+/// 混合模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlendMode {
+    /// 正常
+    #[default]
+    Normal,
+    /// 正片叠底
+    Multiply,
+    /// 滤色
+    Screen,
+    /// 叠加
+    Overlay,
+    /// 柔光
+    SoftLight,
+    /// 强光
+    HardLight,
+    /// 变暗
+    Darken,
+    /// 变亮
+    Lighten,
+    /// 差值
+    Difference,
+    /// 线性减淡 (相加)
+    Add,
+}
+
+impl BlendMode {
+    /// 所有混合模式，顺序与着色器中的编号一致
+    pub const ALL: [BlendMode; 10] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::SoftLight,
+        BlendMode::HardLight,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::Difference,
+        BlendMode::Add,
+    ];
+
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "normal",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+            BlendMode::SoftLight => "soft-light",
+            BlendMode::HardLight => "hard-light",
+            BlendMode::Darken => "darken",
+            BlendMode::Lighten => "lighten",
+            BlendMode::Difference => "difference",
+            BlendMode::Add => "add",
+        }
+    }
+
+    /// 从名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// 着色器中的编号
+    pub fn index(&self) -> u32 {
+        Self::ALL.iter().position(|mode| mode == self).unwrap_or(0) as u32
+    }
+
+    /// 单个通道的混合结果 (取值 0.0 到 1.0)
+    ///
+    /// # 参数
+    /// * `base` - 下层的值
+    /// * `top` - 图层的值
+    pub fn blend(&self, base: f32, top: f32) -> f32 {
+        let (b, s) = (base.clamp(0.0, 1.0), top.clamp(0.0, 1.0));
+        match self {
+            BlendMode::Normal => s,
+            BlendMode::Multiply => b * s,
+            BlendMode::Screen => screen(b, s),
+            BlendMode::Overlay => hard_light(s, b),
+            BlendMode::SoftLight => soft_light(b, s),
+            BlendMode::HardLight => hard_light(b, s),
+            BlendMode::Darken => b.min(s),
+            BlendMode::Lighten => b.max(s),
+            BlendMode::Difference => (b - s).abs(),
+            BlendMode::Add => (b + s).min(1.0),
+        }
+    }
+}
+
+fn screen(b: f32, s: f32) -> f32 {
+    b + s - b * s
+}
+
+fn hard_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b * 2.0 * s
+    } else {
+        screen(b, 2.0 * s - 1.0)
+    }
+}
+
+/// W3C Compositing 规范中的柔光公式
+fn soft_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b - (1.0 - 2.0 * s) * b * (1.0 - b)
+    } else {
+        let d = if b <= 0.25 {
+            ((16.0 * b - 12.0) * b + 4.0) * b
+        } else {
+            b.sqrt()
+        };
+        b + (2.0 * s - 1.0) * (d - b)
+    }
+}
+
+/// 图层在输出图像上的位置
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerTransform {
+    /// 图层中心 (输出图像归一化坐标)
+    pub x: f32,
+    pub y: f32,
+    /// 图层宽度占输出图像宽度的比例，高度按图层图像的宽高比计算
+    pub scale: f32,
+    /// 绕中心的旋转角度 (度，顺时针)
+    pub rotation: f32,
+}
+
+impl LayerTransform {
+    /// 铺满宽度、居中、不旋转
+    pub const fn default() -> Self {
+        Self {
+            x: 0.5,
+            y: 0.5,
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// 限制到有效范围
+    pub fn clamped(self) -> Self {
+        Self {
+            x: self.x.clamp(-1.0, 2.0),
+            y: self.y.clamp(-1.0, 2.0),
+            scale: self.scale.clamp(0.01, 10.0),
+            rotation: self.rotation.rem_euclid(360.0),
+        }
+    }
+}
+
+impl Default for LayerTransform {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// 图层
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    /// 名称
+    pub name: String,
+    /// 图层图像的编号 (由调用方分配和保存像素数据)
+    pub image: u32,
+    /// 位置、缩放和旋转
+    #[serde(default)]
+    pub transform: LayerTransform,
+    /// 不透明度 (0.0 到 1.0)
+    #[serde(default = "full_opacity")]
+    pub opacity: f32,
+    /// 混合模式
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    /// 是否显示
+    #[serde(default = "visible")]
+    pub visible: bool,
+}
+
+fn full_opacity() -> f32 {
+    1.0
+}
+
+fn visible() -> bool {
+    true
+}
+
+impl Layer {
+    /// 创建正常混合、完全不透明的图层
+    pub fn new(name: impl Into<String>, image: u32) -> Self {
+        Self {
+            name: name.into(),
+            image,
+            transform: LayerTransform::default(),
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            visible: true,
+        }
+    }
+
+    /// 设置不透明度
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// 是否对结果有影响
+    pub fn is_visible(&self) -> bool {
+        self.visible && self.opacity > 0.0
+    }
+
+    /// 把图层像素 (线性值和不透明度) 合成到下层像素上
+    ///
+    /// # 参数
+    /// * `base` - 下层的线性值
+    /// * `top` - 图层的线性值
+    /// * `alpha` - 图层像素的不透明度 (不含图层不透明度)
+    pub fn composite(&self, base: [f32; 3], top: [f32; 3], alpha: f32) -> [f32; 3] {
+        let weight = alpha * self.opacity;
+        std::array::from_fn(|c| {
+            let blended = self.blend_mode.blend(base[c], top[c]);
+            base[c] + (blended - base[c]) * weight
+        })
+    }
+
+    /// 堆上额外占用的内存 (字节)
+    pub fn heap_size(&self) -> usize {
+        self.name.capacity()
+    }
+}
@@ -1,8 +1,41 @@
 //! 图像调整参数定义
 
+pub mod clipboard;
+pub mod color;
+pub mod comparison;
+pub mod grading;
+pub mod history;
+pub mod layer;
+pub mod mask;
+pub mod operation;
+pub mod preset;
+pub mod range;
+pub mod recipe;
+pub mod snapshot;
+pub mod state;
+
+pub use clipboard::{AdjustmentClipboard, PasteGroups};
+pub use color::ColorSpace;
+pub use comparison::{ComparisonMode, ComparisonState, SplitOrientation};
+pub use grading::{ColorGrading, ColorWheel, ToneRange};
+pub use history::{AdjustField, EditCommand, EditHistory};
+pub use layer::{BlendMode, Layer, LayerTransform, MAX_LAYERS};
+pub use mask::{BrushStroke, Mask, MaskShape, MAX_BRUSH_MASKS, MAX_MASKS};
+pub use operation::{CropTransform, EditOperation, UNIT_CORNERS};
+pub use preset::{Preset, PresetLibrary};
+pub use range::RangeSelection;
+pub use recipe::{EditRecipe, RECIPE_VERSION};
+pub use snapshot::{Snapshot, VirtualCopy};
+pub use state::EditState;
+
+use serde::{Deserialize, Serialize};
+
 /// 图像调整参数
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Serialize, Deserialize,
+)]
+#[serde(default)]
 pub struct AdjustmentParams {
     /// 亮度 (-1.0 到 1.0)
     pub brightness: f32,
@@ -11,9 +44,16 @@ pub struct AdjustmentParams {
     /// 饱和度 (0.0 到 2.0)
     pub saturation: f32,
     /// 填充对齐
+    #[serde(skip)]
     pub _padding: f32,
 }
 
+impl Default for AdjustmentParams {
+    fn default() -> Self {
+        AdjustmentParams::default()
+    }
+}
+
 impl AdjustmentParams {
     pub const fn default() -> Self {
         Self {
@@ -43,10 +83,18 @@ impl AdjustmentParams {
     pub fn set_saturation(&mut self, value: f32) {
         self.saturation = value.clamp(0.0, 2.0);
     }
+
+    /// 限制到有效范围
+    pub fn clamped(mut self) -> Self {
+        self.set_brightness(self.brightness);
+        self.set_contrast(self.contrast);
+        self.set_saturation(self.saturation);
+        self
+    }
 }
 
 /// 裁剪矩形
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct CropRect {
     /// 归一化 x 坐标 (0-1)
     pub x: f32,
@@ -56,7 +104,7 @@ pub struct CropRect {
     pub width: f32,
     /// 归一化高度 (0-1)
     pub height: f32,
-    /// 宽高比 (None = 自由)
+    /// 像素宽高比 (宽 / 高，None = 自由)
     pub ratio: Option<f32>,
 }
 
@@ -85,4 +133,36 @@ impl CropRect {
             && self.x + self.width <= 1.0
             && self.y + self.height <= 1.0
     }
+
+    /// 把裁剪区域映射到另一种宽高比的图像上
+    ///
+    /// 保持裁剪区域的像素宽高比、中心位置和面积占比，必要时缩小以放进图像。
+    ///
+    /// # 参数
+    /// * `source_aspect` - 原图像的像素宽高比 (宽 / 高)
+    /// * `target_aspect` - 目标图像的像素宽高比 (宽 / 高)
+    pub fn remap(&self, source_aspect: f32, target_aspect: f32) -> CropRect {
+        let pixel_ratio = self.width * source_aspect / self.height;
+        let area = self.width * self.height;
+
+        // 在目标图像上，归一化宽高之比 = 像素宽高比 / 图像宽高比
+        let mut width = (area * pixel_ratio / target_aspect).sqrt();
+        let mut height = (area * target_aspect / pixel_ratio).sqrt();
+        let overflow = width.max(height);
+        if overflow > 1.0 {
+            width /= overflow;
+            height /= overflow;
+        }
+
+        let center_x = self.x + self.width / 2.0;
+        let center_y = self.y + self.height / 2.0;
+
+        CropRect {
+            x: (center_x - width / 2.0).clamp(0.0, 1.0 - width),
+            y: (center_y - height / 2.0).clamp(0.0, 1.0 - height),
+            width,
+            height,
+            ratio: self.ratio,
+        }
+    }
 }
@@ -0,0 +1,315 @@
+//! 局部调整蒙版
+//!
+//! 蒙版位置使用原图的归一化坐标 (0-1)，与 `CropRect` 一样不依赖分辨率，
+//! 并且随几何操作一起移动。每个蒙版带有自己的一组调整参数，
+//! 在全局调整之后按蒙版权重混合。
+//!
+//! 画笔蒙版只保存笔触数据，渲染时按目标分辨率栅格化。
+
+use super::range::smoothstep;
+use super::{AdjustmentParams, RangeSelection};
+use serde::{Deserialize, Serialize};
+
+/// 同时生效的蒙版数量上限 (受 uniform 数组长度限制)
+pub const MAX_MASKS: usize = 8;
+
+/// 画笔蒙版数量上限 (每个画笔蒙版占用蒙版纹理的一个通道)
+pub const MAX_BRUSH_MASKS: usize = 4;
+
+/// 画笔笔触
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrushStroke {
+    /// 笔触经过的点 (原图归一化坐标 0-1)
+    pub points: Vec<[f32; 2]>,
+    /// 笔刷直径 (原图高度的比例)
+    pub size: f32,
+    /// 羽化 (0.0 到 1.0，占半径的比例)
+    pub feather: f32,
+    /// 流量 (0.0 到 1.0)，每个笔刷印记的不透明度，重叠处逐渐累积
+    pub flow: f32,
+    /// 是否为擦除笔触
+    #[serde(default)]
+    pub erase: bool,
+}
+
+impl BrushStroke {
+    pub fn new(size: f32, feather: f32, flow: f32, erase: bool) -> Self {
+        Self {
+            points: Vec::new(),
+            size: size.max(0.0),
+            feather: feather.clamp(0.0, 1.0),
+            flow: flow.clamp(0.0, 1.0),
+            erase,
+        }
+    }
+
+    /// 添加一个点
+    pub fn add_point(&mut self, x: f32, y: f32) {
+        self.points.push([x, y]);
+    }
+
+    /// 限制参数到有效范围
+    fn clamped(self) -> Self {
+        Self {
+            size: self.size.max(0.0),
+            feather: self.feather.clamp(0.0, 1.0),
+            flow: self.flow.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+}
+
+/// 蒙版形状
+///
+/// 距离和角度在按原图宽高比校正后的空间中计算 (单位为原图高度)，
+/// 因此圆形蒙版在非正方形图像上仍然是圆形。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaskShape {
+    /// 线性渐变：穿过中心点、垂直于 `angle` 方向的过渡带，`angle` 指向的一侧完全生效
+    Linear {
+        /// 中心点 x (0.0 到 1.0)
+        x: f32,
+        /// 中心点 y (0.0 到 1.0)
+        y: f32,
+        /// 生效方向 (度，0 指向右侧，顺时针)
+        angle: f32,
+        /// 过渡带宽度 (原图高度的比例)
+        feather: f32,
+    },
+    /// 径向渐变：椭圆内部生效
+    Radial {
+        /// 中心点 x (0.0 到 1.0)
+        x: f32,
+        /// 中心点 y (0.0 到 1.0)
+        y: f32,
+        /// 水平半径 (原图宽度的比例)
+        radius_x: f32,
+        /// 垂直半径 (原图高度的比例)
+        radius_y: f32,
+        /// 旋转角度 (度，顺时针)
+        rotation: f32,
+        /// 羽化 (0.0 到 1.0，占半径的比例)
+        feather: f32,
+    },
+    /// 整张图像 (通常与范围选择一起使用)
+    Global,
+    /// 画笔：按顺序叠加的笔触
+    Brush {
+        /// 笔触
+        strokes: Vec<BrushStroke>,
+    },
+}
+
+impl MaskShape {
+    /// 限制参数到有效范围
+    fn clamped(self) -> Self {
+        match self {
+            MaskShape::Linear {
+                x,
+                y,
+                angle,
+                feather,
+            } => MaskShape::Linear {
+                x,
+                y,
+                angle: angle.rem_euclid(360.0),
+                feather: feather.max(0.0),
+            },
+            MaskShape::Radial {
+                x,
+                y,
+                radius_x,
+                radius_y,
+                rotation,
+                feather,
+            } => MaskShape::Radial {
+                x,
+                y,
+                radius_x: radius_x.max(f32::EPSILON),
+                radius_y: radius_y.max(f32::EPSILON),
+                rotation: rotation.rem_euclid(360.0),
+                feather: feather.clamp(0.0, 1.0),
+            },
+            MaskShape::Brush { strokes } => MaskShape::Brush {
+                strokes: strokes.into_iter().map(BrushStroke::clamped).collect(),
+            },
+            MaskShape::Global => MaskShape::Global,
+        }
+    }
+
+    /// 原图归一化坐标 `(u, v)` 处的覆盖程度 (0.0 到 1.0)
+    ///
+    /// 画笔蒙版需要先栅格化，这里返回 None。
+    ///
+    /// # 参数
+    /// * `aspect` - 原图宽高比 (宽 / 高)
+    pub fn coverage(&self, u: f32, v: f32, aspect: f32) -> Option<f32> {
+        match *self {
+            MaskShape::Linear {
+                x,
+                y,
+                angle,
+                feather,
+            } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let distance = (u - x) * aspect * cos + (v - y) * sin;
+                let half = feather * 0.5;
+                Some(smoothstep(-half, half, distance))
+            }
+            MaskShape::Radial {
+                x,
+                y,
+                radius_x,
+                radius_y,
+                rotation,
+                feather,
+            } => {
+                // 在校正宽高比的空间中反向旋转到椭圆的局部坐标
+                let (sin, cos) = rotation.to_radians().sin_cos();
+                let dx = (u - x) * aspect;
+                let dy = v - y;
+                let local_x = dx * cos + dy * sin;
+                let local_y = -dx * sin + dy * cos;
+                let r =
+                    ((local_x / (radius_x * aspect)).powi(2) + (local_y / radius_y).powi(2)).sqrt();
+                Some(1.0 - smoothstep(1.0 - feather, 1.0, r))
+            }
+            MaskShape::Global => Some(1.0),
+            MaskShape::Brush { .. } => None,
+        }
+    }
+}
+
+/// 局部调整蒙版
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Mask {
+    /// 形状
+    pub shape: MaskShape,
+    /// 是否反转 (作用于形状之外)
+    #[serde(default)]
+    pub invert: bool,
+    /// 与形状相交的亮度/颜色范围选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RangeSelection>,
+    /// 蒙版区域内的调整参数，在全局调整的结果上再应用一次
+    #[serde(default)]
+    pub adjustments: AdjustmentParams,
+}
+
+impl Mask {
+    pub fn new(shape: MaskShape) -> Self {
+        Self {
+            shape: shape.clamped(),
+            invert: false,
+            range: None,
+            adjustments: AdjustmentParams::default(),
+        }
+    }
+
+    /// 线性渐变蒙版
+    pub fn linear(x: f32, y: f32, angle: f32, feather: f32) -> Self {
+        Self::new(MaskShape::Linear {
+            x,
+            y,
+            angle,
+            feather,
+        })
+    }
+
+    /// 径向渐变蒙版
+    pub fn radial(
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        feather: f32,
+    ) -> Self {
+        Self::new(MaskShape::Radial {
+            x,
+            y,
+            radius_x,
+            radius_y,
+            rotation,
+            feather,
+        })
+    }
+
+    /// 设置形状
+    pub fn set_shape(&mut self, shape: MaskShape) {
+        self.shape = shape.clamped();
+    }
+
+    /// 把参数限制到有效范围 (用于反序列化得到的蒙版)，包含非有限值的范围选择被清除
+    pub(crate) fn sanitize(&mut self) {
+        let shape = std::mem::replace(&mut self.shape, MaskShape::Global);
+        self.shape = shape.clamped();
+        self.range = self.range.and_then(RangeSelection::clamped);
+        self.adjustments = self.adjustments.clamped();
+    }
+
+    /// 空白的画笔蒙版
+    pub fn brush() -> Self {
+        Self::new(MaskShape::Brush {
+            strokes: Vec::new(),
+        })
+    }
+
+    /// 是否为画笔蒙版
+    pub fn is_brush(&self) -> bool {
+        matches!(self.shape, MaskShape::Brush { .. })
+    }
+
+    /// 给画笔蒙版添加笔触，不是画笔蒙版时返回 false
+    pub fn add_stroke(&mut self, stroke: BrushStroke) -> bool {
+        match &mut self.shape {
+            MaskShape::Brush { strokes } => {
+                strokes.push(stroke);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 由形状覆盖程度和像素颜色得到蒙版权重 (先处理反转，再与范围选择相交)
+    ///
+    /// # 参数
+    /// * `rgb` - 原图像素的线性 RGB 值
+    pub fn weight_from_coverage(&self, coverage: f32, rgb: [f32; 3]) -> f32 {
+        let shape = if self.invert {
+            1.0 - coverage
+        } else {
+            coverage
+        };
+        match &self.range {
+            Some(range) if shape > 0.0 => shape * range.weight(rgb),
+            _ => shape,
+        }
+    }
+
+    /// 原图归一化坐标 `(u, v)` 处的蒙版权重 (0.0 到 1.0)，画笔蒙版返回 None
+    ///
+    /// # 参数
+    /// * `aspect` - 原图宽高比 (宽 / 高)
+    /// * `rgb` - 该位置原图像素的线性 RGB 值
+    pub fn weight(&self, u: f32, v: f32, aspect: f32, rgb: [f32; 3]) -> Option<f32> {
+        self.shape
+            .coverage(u, v, aspect)
+            .map(|coverage| self.weight_from_coverage(coverage, rgb))
+    }
+
+    /// 堆上额外占用的内存 (字节)
+    pub fn heap_size(&self) -> usize {
+        match &self.shape {
+            MaskShape::Brush { strokes } => strokes
+                .iter()
+                .map(|stroke| {
+                    std::mem::size_of::<BrushStroke>()
+                        + stroke.points.capacity() * std::mem::size_of::<[f32; 2]>()
+                })
+                .sum(),
+            _ => 0,
+        }
+    }
+}
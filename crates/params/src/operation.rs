@@ -0,0 +1,113 @@
+//! 几何编辑操作
+
+use super::CropRect;
+use serde::{Deserialize, Serialize};
+
+/// 几何编辑操作
+///
+/// 操作按顺序作用于原图，每个操作的参数都相对于前一个操作的输出。
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditOperation {
+    /// 裁剪
+    Crop(CropRect),
+    /// 旋转 (度，顺时针)
+    Rotate { degrees: f32 },
+    /// 翻转
+    Flip { horizontal: bool, vertical: bool },
+    /// 拉直裁剪：旋转和裁剪合成一个操作，只重采样一次
+    StraightenCrop(CropTransform),
+    /// 四点透视校正：输入图像中的四边形 (左上、右上、右下、左下，归一化坐标)
+    /// 被拉伸为矩形，输出尺寸由四边形的边长决定
+    Perspective { corners: [[f32; 2]; 4] },
+    /// 梯形校正：竖直和水平方向 (-1.0 到 1.0)，输出尺寸与输入相同
+    ///
+    /// 竖直方向为正时拉宽图像顶部 (校正仰拍建筑的汇聚竖线)，
+    /// 水平方向为正时拉高图像左侧。
+    Keystone { vertical: f32, horizontal: f32 },
+}
+
+/// 不做透视校正的四个角 (左上、右上、右下、左下)
+pub const UNIT_CORNERS: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+/// 带旋转角度的裁剪
+///
+/// 图像绕裁剪区域中心顺时针旋转 `angle` 度后，取出 `rect` 大小的正立区域。
+/// `rect` 的中心和尺寸都是输入图像的归一化坐标，宽高比按像素计算。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CropTransform {
+    /// 裁剪区域 (旋转前，以其中心为旋转中心)
+    pub rect: CropRect,
+    /// 旋转角度 (度，顺时针)
+    #[serde(default)]
+    pub angle: f32,
+}
+
+impl CropTransform {
+    pub fn new(rect: CropRect, angle: f32) -> Self {
+        Self { rect, angle }
+    }
+
+    /// 裁剪区域中心 (归一化坐标)
+    pub fn center(&self) -> (f32, f32) {
+        (
+            self.rect.x + self.rect.width / 2.0,
+            self.rect.y + self.rect.height / 2.0,
+        )
+    }
+}
+
+impl EditOperation {
+    /// 检查并限制参数 (用于反序列化得到的操作)
+    ///
+    /// 与逐项设置时的检查一致：无效的裁剪区域或非有限的角度返回 None，
+    /// 透视角点和梯形校正被限制到有效范围。
+    pub fn validated(self) -> Option<Self> {
+        match self {
+            EditOperation::Crop(rect) => rect.is_valid().then_some(self),
+            EditOperation::Rotate { degrees } => degrees.is_finite().then_some(self),
+            EditOperation::Flip { .. } => Some(self),
+            EditOperation::StraightenCrop(crop) => {
+                (crop.rect.is_valid() && crop.angle.is_finite()).then_some(self)
+            }
+            EditOperation::Perspective { corners } => {
+                corners.iter().flatten().all(|v| v.is_finite()).then(|| {
+                    EditOperation::Perspective {
+                        corners: corners.map(|[x, y]| [x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)]),
+                    }
+                })
+            }
+            EditOperation::Keystone {
+                vertical,
+                horizontal,
+            } => {
+                (vertical.is_finite() && horizontal.is_finite()).then(|| EditOperation::Keystone {
+                    vertical: vertical.clamp(-1.0, 1.0),
+                    horizontal: horizontal.clamp(-1.0, 1.0),
+                })
+            }
+        }
+    }
+
+    /// 操作是否不产生任何效果
+    pub fn is_identity(&self) -> bool {
+        match *self {
+            EditOperation::Crop(rect) => {
+                rect.x == 0.0 && rect.y == 0.0 && rect.width == 1.0 && rect.height == 1.0
+            }
+            EditOperation::Rotate { degrees } => degrees.rem_euclid(360.0) == 0.0,
+            EditOperation::Flip {
+                horizontal,
+                vertical,
+            } => !horizontal && !vertical,
+            EditOperation::StraightenCrop(crop) => {
+                crop.angle.rem_euclid(360.0) == 0.0 && EditOperation::Crop(crop.rect).is_identity()
+            }
+            EditOperation::Perspective { corners } => corners == UNIT_CORNERS,
+            EditOperation::Keystone {
+                vertical,
+                horizontal,
+            } => vertical == 0.0 && horizontal == 0.0,
+        }
+    }
+}
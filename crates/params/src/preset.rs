@@ -0,0 +1,242 @@
+//! 预设定义
+//!
+//! 预设只保存部分调整字段，应用时按强度在当前值和预设值之间插值，
+//! 未保存的字段保持不变。
+
+use super::{ColorWheel, EditState, ToneRange};
+use serde::{Deserialize, Serialize};
+
+/// 预设
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    /// 名称 (在预设库中唯一)
+    pub name: String,
+    /// 亮度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<f32>,
+    /// 对比度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contrast: Option<f32>,
+    /// 饱和度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saturation: Option<f32>,
+    /// 阴影色轮
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadows: Option<ColorWheel>,
+    /// 中间调色轮
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub midtones: Option<ColorWheel>,
+    /// 高光色轮
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<ColorWheel>,
+    /// 全局色轮
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global: Option<ColorWheel>,
+    /// 色彩分级混合程度
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blending: Option<f32>,
+    /// 色彩分级平衡
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<f32>,
+}
+
+impl Preset {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 从编辑状态创建包含全部调整字段的预设
+    pub fn from_state(name: &str, state: &EditState) -> Self {
+        let grading = &state.grading;
+        Self {
+            name: name.to_string(),
+            brightness: Some(state.adjustments.brightness),
+            contrast: Some(state.adjustments.contrast),
+            saturation: Some(state.adjustments.saturation),
+            shadows: Some(grading.shadows),
+            midtones: Some(grading.midtones),
+            highlights: Some(grading.highlights),
+            global: Some(grading.global),
+            blending: Some(grading.blending),
+            balance: Some(grading.balance),
+        }
+    }
+
+    /// 按强度应用预设
+    ///
+    /// # 参数
+    /// * `intensity` - 强度 (0.0 到 1.0)，0 保持当前值，1 完全使用预设值
+    pub fn apply(&self, state: &mut EditState, intensity: f32) {
+        let t = intensity.clamp(0.0, 1.0);
+        let adjustments = &mut state.adjustments;
+        if let Some(value) = self.brightness {
+            adjustments.set_brightness(lerp(adjustments.brightness, value, t));
+        }
+        if let Some(value) = self.contrast {
+            adjustments.set_contrast(lerp(adjustments.contrast, value, t));
+        }
+        if let Some(value) = self.saturation {
+            adjustments.set_saturation(lerp(adjustments.saturation, value, t));
+        }
+
+        let grading = &mut state.grading;
+        let wheels = [
+            (ToneRange::Shadows, self.shadows),
+            (ToneRange::Midtones, self.midtones),
+            (ToneRange::Highlights, self.highlights),
+            (ToneRange::Global, self.global),
+        ];
+        for (range, wheel) in wheels {
+            if let Some(target) = wheel {
+                let current = *grading.wheel(range);
+                // 当前色轮没有饱和度时色相无意义，直接使用预设色相
+                let hue = if current.saturation == 0.0 {
+                    target.hue
+                } else {
+                    lerp_hue(current.hue, target.hue, t)
+                };
+                grading.set_wheel(
+                    range,
+                    hue,
+                    lerp(current.saturation, target.saturation, t),
+                    lerp(current.luminance, target.luminance, t),
+                );
+            }
+        }
+        if let Some(value) = self.blending {
+            grading.set_blending(lerp(grading.blending, value, t));
+        }
+        if let Some(value) = self.balance {
+            grading.set_balance(lerp(grading.balance, value, t));
+        }
+    }
+}
+
+/// 预设库
+#[derive(Clone, Debug, Default)]
+pub struct PresetLibrary {
+    presets: Vec<Preset>,
+}
+
+impl PresetLibrary {
+    /// 创建空的预设库
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建包含内置预设的预设库
+    pub fn with_builtins() -> Self {
+        Self {
+            presets: builtin_presets(),
+        }
+    }
+
+    /// 添加预设，同名预设会被替换
+    pub fn add(&mut self, preset: Preset) {
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// 删除预设
+    pub fn remove(&mut self, name: &str) -> Option<Preset> {
+        let index = self.presets.iter().position(|p| p.name == name)?;
+        Some(self.presets.remove(index))
+    }
+
+    /// 按名称获取预设
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// 所有预设名称
+    pub fn names(&self) -> Vec<String> {
+        self.presets.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// 所有预设
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    /// 从 JSON 导入预设 (单个预设对象或预设数组)，返回导入的数量
+    pub fn import_json(&mut self, json: &str) -> Result<usize, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid preset JSON: {}", e))?;
+        let presets: Vec<Preset> = if value.is_array() {
+            serde_json::from_value(value)
+        } else {
+            serde_json::from_value(value).map(|preset| vec![preset])
+        }
+        .map_err(|e| format!("Invalid preset: {}", e))?;
+
+        if presets.iter().any(|p| p.name.is_empty()) {
+            return Err("Invalid preset: missing name".to_string());
+        }
+
+        let count = presets.len();
+        for preset in presets {
+            self.add(preset);
+        }
+        Ok(count)
+    }
+
+    /// 导出全部预设为 JSON 数组
+    pub fn export_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.presets)
+            .map_err(|e| format!("Failed to serialize presets: {}", e))
+    }
+}
+
+/// 内置预设
+fn builtin_presets() -> Vec<Preset> {
+    vec![
+        Preset {
+            contrast: Some(1.2),
+            saturation: Some(1.35),
+            ..Preset::new("Vivid")
+        },
+        Preset {
+            contrast: Some(0.8),
+            saturation: Some(0.85),
+            shadows: Some(ColorWheel::new(220.0, 0.1, 0.15)),
+            ..Preset::new("Matte")
+        },
+        Preset {
+            contrast: Some(1.25),
+            saturation: Some(0.0),
+            ..Preset::new("Black & White")
+        },
+        Preset {
+            global: Some(ColorWheel::new(35.0, 0.2, 0.0)),
+            highlights: Some(ColorWheel::new(45.0, 0.15, 0.0)),
+            ..Preset::new("Warm")
+        },
+        Preset {
+            global: Some(ColorWheel::new(205.0, 0.2, 0.0)),
+            shadows: Some(ColorWheel::new(220.0, 0.15, 0.0)),
+            ..Preset::new("Cool")
+        },
+        Preset {
+            contrast: Some(1.15),
+            shadows: Some(ColorWheel::new(190.0, 0.35, 0.0)),
+            highlights: Some(ColorWheel::new(35.0, 0.3, 0.0)),
+            ..Preset::new("Teal & Orange")
+        },
+    ]
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// 沿最短方向插值色相 (度)
+fn lerp_hue(from: f32, to: f32, t: f32) -> f32 {
+    let delta = (to - from + 180.0).rem_euclid(360.0) - 180.0;
+    (from + delta * t).rem_euclid(360.0)
+}
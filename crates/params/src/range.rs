@@ -0,0 +1,123 @@
+//! 亮度范围和颜色范围选择
+//!
+//! 范围选择按原图像素的线性值计算权重，可以单独使用 (配合 `MaskShape::Global`)，
+//! 也可以与渐变、画笔蒙版相交。`image.wgsl` 中的 `range_weight` 使用相同的公式，
+//! 保证预览和导出一致。
+
+use serde::{Deserialize, Serialize};
+
+/// 计算亮度使用的系数 (与着色器中的 `LUMA` 一致)
+const LUMA: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// 把线性值转换为近似感知均匀的值时使用的指数
+const PERCEPTUAL_GAMMA: f32 = 1.0 / 2.2;
+
+/// 范围选择
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RangeSelection {
+    /// 亮度范围 (感知亮度 0.0 到 1.0)
+    Luminance {
+        /// 下限
+        low: f32,
+        /// 上限
+        high: f32,
+        /// 范围两端的过渡宽度
+        smoothness: f32,
+    },
+    /// 颜色范围
+    Color {
+        /// 取样颜色 (工作色彩空间的线性 RGB)
+        color: [f32; 3],
+        /// 容差 (0.0 到 1.0)
+        tolerance: f32,
+        /// 容差之外的过渡宽度
+        smoothness: f32,
+    },
+}
+
+impl RangeSelection {
+    /// 亮度范围，参数不是有限值时返回 None
+    pub fn luminance(low: f32, high: f32, smoothness: f32) -> Option<Self> {
+        if ![low, high, smoothness].iter().all(|v| v.is_finite()) {
+            return None;
+        }
+        let low = low.clamp(0.0, 1.0);
+        Some(RangeSelection::Luminance {
+            low,
+            high: high.clamp(low, 1.0),
+            smoothness: smoothness.clamp(0.0, 1.0),
+        })
+    }
+
+    /// 颜色范围，参数不是有限值时返回 None
+    pub fn color(color: [f32; 3], tolerance: f32, smoothness: f32) -> Option<Self> {
+        if !color
+            .iter()
+            .chain([&tolerance, &smoothness])
+            .all(|v| v.is_finite())
+        {
+            return None;
+        }
+        Some(RangeSelection::Color {
+            color: color.map(|c| c.clamp(0.0, 1.0)),
+            tolerance: tolerance.clamp(0.0, 1.0),
+            smoothness: smoothness.clamp(0.0, 1.0),
+        })
+    }
+
+    /// 限制到有效范围 (与构造函数相同)，用于反序列化得到的选择
+    pub fn clamped(self) -> Option<Self> {
+        match self {
+            RangeSelection::Luminance {
+                low,
+                high,
+                smoothness,
+            } => Self::luminance(low, high, smoothness),
+            RangeSelection::Color {
+                color,
+                tolerance,
+                smoothness,
+            } => Self::color(color, tolerance, smoothness),
+        }
+    }
+
+    /// 线性 RGB 像素的选择权重 (0.0 到 1.0)
+    pub fn weight(&self, rgb: [f32; 3]) -> f32 {
+        match *self {
+            RangeSelection::Luminance {
+                low,
+                high,
+                smoothness,
+            } => {
+                let luma = rgb[0] * LUMA[0] + rgb[1] * LUMA[1] + rgb[2] * LUMA[2];
+                let lightness = luma.clamp(0.0, 1.0).powf(PERCEPTUAL_GAMMA);
+                smoothstep(low - smoothness, low, lightness)
+                    * (1.0 - smoothstep(high, high + smoothness, lightness))
+            }
+            RangeSelection::Color {
+                color,
+                tolerance,
+                smoothness,
+            } => {
+                let mut sum = 0.0;
+                for (value, target) in rgb.iter().zip(color) {
+                    let delta = value.clamp(0.0, 1.0).powf(PERCEPTUAL_GAMMA)
+                        - target.powf(PERCEPTUAL_GAMMA);
+                    sum += delta * delta;
+                }
+                let distance = (sum / 3.0).sqrt();
+                1.0 - smoothstep(tolerance, tolerance + smoothness, distance)
+            }
+        }
+    }
+}
+
+/// 与 WGSL 的 `smoothstep` 相同，`edge0 == edge1` 时退化为阶跃
+pub(crate) fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge1 <= edge0 {
+        return if x < edge0 { 0.0 } else { 1.0 };
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
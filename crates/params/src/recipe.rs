@@ -0,0 +1,123 @@
+//! 编辑配方 (可保存的编辑状态)
+//!
+//! 配方是带版本号的 JSON 文档。读取时先按版本号逐级迁移到当前格式，
+//! 再反序列化；未知字段会被忽略，缺失字段使用默认值。
+
+use super::EditState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 当前配方格式版本
+pub const RECIPE_VERSION: u32 = 2;
+
+/// 迁移步骤，`MIGRATIONS[i]` 把版本 `i + 1` 的文档升级到版本 `i + 2`
+///
+/// 修改配方结构时递增 `RECIPE_VERSION` 并在末尾追加对应的迁移函数。
+const MIGRATIONS: &[fn(&mut Value)] = &[migrate_v1_to_v2];
+
+/// 编辑配方
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EditRecipe {
+    /// 格式版本
+    pub version: u32,
+    /// 编辑状态
+    #[serde(flatten)]
+    pub state: EditState,
+}
+
+impl EditRecipe {
+    /// 用当前格式版本创建配方
+    pub fn new(state: EditState) -> Self {
+        Self {
+            version: RECIPE_VERSION,
+            state,
+        }
+    }
+
+    /// 序列化为 JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize recipe: {}", e))
+    }
+
+    /// 从 JSON 解析，旧版本会先迁移到当前格式
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid recipe JSON: {}", e))?;
+        Self::from_value(value)
+    }
+
+    /// 从 JSON 值解析，旧版本会先迁移到当前格式，编辑状态被限制到有效范围
+    pub fn from_value(mut value: Value) -> Result<Self, String> {
+        migrate(&mut value)?;
+        let mut recipe: Self =
+            serde_json::from_value(value).map_err(|e| format!("Invalid recipe: {}", e))?;
+        recipe.state.sanitize();
+        Ok(recipe)
+    }
+}
+
+/// 把配方文档迁移到当前格式版本
+fn migrate(value: &mut Value) -> Result<(), String> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "Invalid recipe: expected a JSON object".to_string())?;
+
+    let version = match object.get("version") {
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|&version| version >= 1)
+            .ok_or_else(|| format!("Invalid recipe version: {}", version))?,
+        None => return Err("Invalid recipe: missing version".to_string()),
+    };
+
+    if version > RECIPE_VERSION {
+        return Err(format!(
+            "Recipe version {} is newer than supported version {}",
+            version, RECIPE_VERSION
+        ));
+    }
+
+    for step in &MIGRATIONS[version as usize - 1..] {
+        step(value);
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), Value::from(RECIPE_VERSION));
+    }
+    Ok(())
+}
+
+/// 版本 1 -> 2：固定的 `crop`/`rotation`/`flip_*` 字段改为有序的几何操作列表
+///
+/// 版本 1 的含义是先翻转、再旋转、最后裁剪。
+fn migrate_v1_to_v2(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+
+    let flip_horizontal = object.remove("flip_horizontal");
+    let flip_vertical = object.remove("flip_vertical");
+    let rotation = object.remove("rotation");
+    let crop = object.remove("crop");
+
+    let mut operations = Vec::new();
+    let horizontal = flip_horizontal.and_then(|v| v.as_bool()).unwrap_or(false);
+    let vertical = flip_vertical.and_then(|v| v.as_bool()).unwrap_or(false);
+    if horizontal || vertical {
+        operations.push(serde_json::json!({
+            "type": "flip",
+            "horizontal": horizontal,
+            "vertical": vertical,
+        }));
+    }
+    if let Some(degrees) = rotation.and_then(|v| v.as_f64()).filter(|d| *d != 0.0) {
+        operations.push(serde_json::json!({ "type": "rotate", "degrees": degrees }));
+    }
+    if let Some(Value::Object(mut crop)) = crop {
+        crop.insert("type".to_string(), Value::from("crop"));
+        operations.push(Value::Object(crop));
+    }
+
+    object.insert("operations".to_string(), Value::Array(operations));
+}
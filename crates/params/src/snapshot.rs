@@ -0,0 +1,78 @@
+//! 快照和虚拟副本
+//!
+//! 虚拟副本与其他副本共享同一张原图，但拥有独立的编辑状态、历史记录和快照，
+//! 例如同一张照片的黑白版本和彩色版本。快照保存某一时刻的完整编辑状态。
+
+use super::{EditHistory, EditState};
+use serde::{Deserialize, Serialize};
+
+/// 命名的编辑状态快照
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// 名称 (在同一副本中唯一)
+    pub name: String,
+    /// 完整的编辑状态
+    pub state: EditState,
+}
+
+/// 虚拟副本
+#[derive(Clone, Debug)]
+pub struct VirtualCopy {
+    /// 名称
+    pub name: String,
+    /// 编辑状态
+    pub state: EditState,
+    /// 撤销/重做历史
+    pub history: EditHistory,
+    snapshots: Vec<Snapshot>,
+}
+
+impl VirtualCopy {
+    /// 创建没有任何编辑的副本
+    pub fn new(name: &str) -> Self {
+        Self::from_state(name, EditState::default())
+    }
+
+    /// 以指定编辑状态创建副本 (历史记录为空)
+    pub fn from_state(name: &str, state: EditState) -> Self {
+        Self {
+            name: name.to_string(),
+            state,
+            history: EditHistory::new(),
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// 保存当前编辑状态为快照，同名快照会被替换
+    pub fn take_snapshot(&mut self, name: &str) {
+        let snapshot = Snapshot {
+            name: name.to_string(),
+            state: self.state.clone(),
+        };
+        match self.snapshots.iter_mut().find(|s| s.name == name) {
+            Some(existing) => *existing = snapshot,
+            None => self.snapshots.push(snapshot),
+        }
+    }
+
+    /// 按名称获取快照
+    pub fn snapshot(&self, name: &str) -> Option<&Snapshot> {
+        self.snapshots.iter().find(|s| s.name == name)
+    }
+
+    /// 删除快照
+    pub fn remove_snapshot(&mut self, name: &str) -> Option<Snapshot> {
+        let index = self.snapshots.iter().position(|s| s.name == name)?;
+        Some(self.snapshots.remove(index))
+    }
+
+    /// 所有快照 (按创建顺序)
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+
+    /// 所有快照名称
+    pub fn snapshot_names(&self) -> Vec<String> {
+        self.snapshots.iter().map(|s| s.name.clone()).collect()
+    }
+}
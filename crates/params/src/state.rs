@@ -0,0 +1,416 @@
+//! 编辑状态定义
+
+use super::{
+    AdjustmentParams, BrushStroke, ColorGrading, CropRect, CropTransform, EditOperation, Layer,
+    Mask, MAX_BRUSH_MASKS, MAX_LAYERS, MAX_MASKS,
+};
+use serde::{Deserialize, Serialize};
+
+/// 一张图像的完整编辑状态
+///
+/// 编辑状态不包含任何像素数据：渲染时总是从未修改的原图开始，
+/// 先按顺序应用几何操作，再应用调整参数。调整参数逐像素作用，
+/// 与几何操作之间的先后顺序不影响结果。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditState {
+    /// 基础调整参数
+    pub adjustments: AdjustmentParams,
+    /// 色彩分级参数
+    pub grading: ColorGrading,
+    /// 按顺序应用的几何操作
+    pub operations: Vec<EditOperation>,
+    /// 局部调整蒙版
+    pub masks: Vec<Mask>,
+    /// 叠加在编辑结果上的图层，从下到上
+    pub layers: Vec<Layer>,
+}
+
+impl EditState {
+    /// 重置为默认值
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 当前的裁剪区域 (最后一个裁剪操作)
+    pub fn crop(&self) -> Option<CropRect> {
+        self.operations.iter().rev().find_map(|op| match op {
+            EditOperation::Crop(rect) => Some(*rect),
+            _ => None,
+        })
+    }
+
+    /// 设置裁剪区域，无效的区域会被忽略
+    ///
+    /// 替换最后一个裁剪操作，没有时追加到末尾；`None` 删除所有裁剪操作。
+    pub fn set_crop(&mut self, crop: Option<CropRect>) {
+        match crop {
+            None => self
+                .operations
+                .retain(|op| !matches!(op, EditOperation::Crop(_))),
+            Some(rect) if rect.is_valid() => {
+                let last = self
+                    .operations
+                    .iter_mut()
+                    .rev()
+                    .find(|op| matches!(op, EditOperation::Crop(_)));
+                match last {
+                    Some(op) => *op = EditOperation::Crop(rect),
+                    None => self.operations.push(EditOperation::Crop(rect)),
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// 当前的拉直裁剪 (最后一个拉直裁剪操作)
+    pub fn crop_transform(&self) -> Option<CropTransform> {
+        self.operations.iter().rev().find_map(|op| match op {
+            EditOperation::StraightenCrop(crop) => Some(*crop),
+            _ => None,
+        })
+    }
+
+    /// 设置拉直裁剪，无效的区域会被忽略
+    ///
+    /// 替换最后一个拉直裁剪操作，没有时追加到末尾；`None` 删除所有拉直裁剪操作。
+    pub fn set_crop_transform(&mut self, crop: Option<CropTransform>) {
+        match crop {
+            None => self
+                .operations
+                .retain(|op| !matches!(op, EditOperation::StraightenCrop(_))),
+            Some(crop) if crop.rect.is_valid() => {
+                let last = self
+                    .operations
+                    .iter_mut()
+                    .rev()
+                    .find(|op| matches!(op, EditOperation::StraightenCrop(_)));
+                match last {
+                    Some(op) => *op = EditOperation::StraightenCrop(crop),
+                    None => self.operations.push(EditOperation::StraightenCrop(crop)),
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// 裁剪操作的位置，没有时为操作栈的末尾 (即新操作会追加的位置)
+    pub fn crop_index(&self) -> usize {
+        self.operations
+            .iter()
+            .rposition(|op| matches!(op, EditOperation::Crop(_)))
+            .unwrap_or(self.operations.len())
+    }
+
+    /// 拉直裁剪操作的位置，没有时为操作栈的末尾 (即新操作会追加的位置)
+    pub fn crop_transform_index(&self) -> usize {
+        self.operations
+            .iter()
+            .rposition(|op| matches!(op, EditOperation::StraightenCrop(_)))
+            .unwrap_or(self.operations.len())
+    }
+
+    /// 总旋转角度 (0.0 到 360.0 度，顺时针)
+    pub fn rotation(&self) -> f32 {
+        let total: f32 = self
+            .operations
+            .iter()
+            .map(|op| match op {
+                EditOperation::Rotate { degrees } => *degrees,
+                _ => 0.0,
+            })
+            .sum();
+        total.rem_euclid(360.0)
+    }
+
+    /// 设置总旋转角度
+    ///
+    /// 调整最后一个旋转操作使总角度等于 `degrees`；没有旋转操作时，
+    /// 在第一个裁剪操作之前插入一个。非有限的角度被忽略。
+    pub fn set_rotation(&mut self, degrees: f32) {
+        if !degrees.is_finite() {
+            return;
+        }
+        let delta = degrees - self.rotation();
+        if delta.rem_euclid(360.0) == 0.0 {
+            return;
+        }
+
+        let last = self
+            .operations
+            .iter()
+            .rposition(|op| matches!(op, EditOperation::Rotate { .. }));
+        match last {
+            Some(index) => {
+                if let EditOperation::Rotate { degrees } = &mut self.operations[index] {
+                    *degrees = (*degrees + delta).rem_euclid(360.0);
+                }
+                if self.operations[index].is_identity() {
+                    self.operations.remove(index);
+                }
+            }
+            None => {
+                let index = self
+                    .operations
+                    .iter()
+                    .position(|op| matches!(op, EditOperation::Crop(_)))
+                    .unwrap_or(self.operations.len());
+                self.operations.insert(
+                    index,
+                    EditOperation::Rotate {
+                        degrees: delta.rem_euclid(360.0),
+                    },
+                );
+            }
+        }
+    }
+
+    /// 当前的翻转状态 (水平, 垂直)
+    pub fn flip(&self) -> (bool, bool) {
+        self.operations
+            .iter()
+            .fold((false, false), |(h, v), op| match op {
+                EditOperation::Flip {
+                    horizontal,
+                    vertical,
+                } => (h ^ horizontal, v ^ vertical),
+                _ => (h, v),
+            })
+    }
+
+    /// 设置翻转状态
+    ///
+    /// 末尾是翻转操作时与之合并，否则追加一个翻转操作。
+    pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+        let (current_h, current_v) = self.flip();
+        let toggle_h = horizontal != current_h;
+        let toggle_v = vertical != current_v;
+        if !toggle_h && !toggle_v {
+            return;
+        }
+
+        if let Some(EditOperation::Flip {
+            horizontal,
+            vertical,
+        }) = self.operations.last_mut()
+        {
+            *horizontal ^= toggle_h;
+            *vertical ^= toggle_v;
+            if !*horizontal && !*vertical {
+                self.operations.pop();
+            }
+        } else {
+            self.operations.push(EditOperation::Flip {
+                horizontal: toggle_h,
+                vertical: toggle_v,
+            });
+        }
+    }
+
+    /// 当前的四点透视校正角点 (最后一个四点透视操作)
+    pub fn perspective(&self) -> Option<[[f32; 2]; 4]> {
+        self.operations.iter().rev().find_map(|op| match op {
+            EditOperation::Perspective { corners } => Some(*corners),
+            _ => None,
+        })
+    }
+
+    /// 设置四点透视校正
+    ///
+    /// 替换最后一个四点透视操作，没有时在第一个裁剪操作之前插入；
+    /// `None` 或不做校正的角点删除所有四点透视操作。
+    pub fn set_perspective(&mut self, corners: Option<[[f32; 2]; 4]>) {
+        let operation = corners.map(|corners| EditOperation::Perspective { corners });
+        self.replace_operation(operation, |op| {
+            matches!(op, EditOperation::Perspective { .. })
+        });
+    }
+
+    /// 当前的梯形校正 (竖直, 水平)
+    pub fn keystone(&self) -> (f32, f32) {
+        self.operations
+            .iter()
+            .rev()
+            .find_map(|op| match *op {
+                EditOperation::Keystone {
+                    vertical,
+                    horizontal,
+                } => Some((vertical, horizontal)),
+                _ => None,
+            })
+            .unwrap_or((0.0, 0.0))
+    }
+
+    /// 设置梯形校正 (-1.0 到 1.0)
+    ///
+    /// 替换最后一个梯形校正操作，没有时在第一个裁剪操作之前插入；两个方向都为 0 时删除。
+    pub fn set_keystone(&mut self, vertical: f32, horizontal: f32) {
+        let operation = EditOperation::Keystone {
+            vertical,
+            horizontal,
+        };
+        self.replace_operation(Some(operation), |op| {
+            matches!(op, EditOperation::Keystone { .. })
+        });
+    }
+
+    /// 替换最后一个匹配的操作，没有时在第一个裁剪操作之前插入；
+    /// `None` 或无效果的操作删除所有匹配的操作
+    ///
+    /// 操作经过 [`EditOperation::validated`] 限制到有效范围，包含非有限值时被忽略。
+    fn replace_operation(
+        &mut self,
+        operation: Option<EditOperation>,
+        matches: impl Fn(&EditOperation) -> bool,
+    ) {
+        let operation = match operation.map(EditOperation::validated) {
+            Some(None) => return,
+            operation => operation.flatten(),
+        };
+        let Some(operation) = operation.filter(|op| !op.is_identity()) else {
+            self.operations.retain(|op| !matches(op));
+            return;
+        };
+        match self.operations.iter().rposition(&matches) {
+            Some(index) => self.operations[index] = operation,
+            None => {
+                let index = self
+                    .operations
+                    .iter()
+                    .position(|op| {
+                        matches!(
+                            op,
+                            EditOperation::Crop(_) | EditOperation::StraightenCrop(_)
+                        )
+                    })
+                    .unwrap_or(self.operations.len());
+                self.operations.insert(index, operation);
+            }
+        }
+    }
+
+    /// 追加几何操作
+    pub fn push_operation(&mut self, operation: EditOperation) {
+        self.operations.push(operation);
+    }
+
+    /// 删除指定位置的几何操作
+    pub fn remove_operation(&mut self, index: usize) -> Option<EditOperation> {
+        (index < self.operations.len()).then(|| self.operations.remove(index))
+    }
+
+    /// 移动几何操作到新的位置
+    pub fn move_operation(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.operations.len() || to >= self.operations.len() {
+            return false;
+        }
+        let operation = self.operations.remove(from);
+        self.operations.insert(to, operation);
+        true
+    }
+
+    /// 添加蒙版，返回其序号；达到数量上限时返回 None
+    pub fn add_mask(&mut self, mask: Mask) -> Option<usize> {
+        let brushes = self.masks.iter().filter(|m| m.is_brush()).count();
+        if self.masks.len() >= MAX_MASKS || (mask.is_brush() && brushes >= MAX_BRUSH_MASKS) {
+            return None;
+        }
+        self.masks.push(mask);
+        Some(self.masks.len() - 1)
+    }
+
+    /// 把反序列化得到的状态 (配方、剪贴板或外部传入的状态) 限制到有效范围
+    ///
+    /// 与逐项修改时的检查一致：调整参数被限制到有效范围，无效的几何操作
+    /// (如越界的裁剪区域) 被丢弃；超出数量上限的蒙版和图层被丢弃 (保留靠前的)，
+    /// 蒙版形状、范围选择和图层参数被限制到有效范围，保证预览和导出处理相同的内容。
+    pub fn sanitize(&mut self) {
+        self.adjustments = self.adjustments.clamped();
+        self.grading = self.grading.clamped();
+        self.operations = self
+            .operations
+            .drain(..)
+            .filter_map(EditOperation::validated)
+            .collect();
+
+        let mut brushes = 0;
+        let mut kept = 0;
+        self.masks.retain(|mask| {
+            if kept >= MAX_MASKS || (mask.is_brush() && brushes >= MAX_BRUSH_MASKS) {
+                return false;
+            }
+            brushes += usize::from(mask.is_brush());
+            kept += 1;
+            true
+        });
+        for mask in &mut self.masks {
+            mask.sanitize();
+        }
+
+        self.layers.truncate(MAX_LAYERS);
+        for layer in &mut self.layers {
+            layer.transform = layer.transform.clamped();
+            layer.set_opacity(layer.opacity);
+        }
+    }
+
+    /// 删除蒙版
+    pub fn remove_mask(&mut self, index: usize) -> Option<Mask> {
+        (index < self.masks.len()).then(|| self.masks.remove(index))
+    }
+
+    /// 给画笔蒙版添加笔触
+    pub fn add_brush_stroke(&mut self, index: usize, stroke: BrushStroke) -> bool {
+        self.masks
+            .get_mut(index)
+            .is_some_and(|mask| mask.add_stroke(stroke))
+    }
+
+    /// 获取蒙版 (可变)
+    pub fn mask_mut(&mut self, index: usize) -> Option<&mut Mask> {
+        self.masks.get_mut(index)
+    }
+
+    /// 添加图层到最上面，返回其序号；达到数量上限时返回 None
+    pub fn add_layer(&mut self, layer: Layer) -> Option<usize> {
+        if self.layers.len() >= MAX_LAYERS {
+            return None;
+        }
+        self.layers.push(layer);
+        Some(self.layers.len() - 1)
+    }
+
+    /// 删除图层
+    pub fn remove_layer(&mut self, index: usize) -> Option<Layer> {
+        (index < self.layers.len()).then(|| self.layers.remove(index))
+    }
+
+    /// 移动图层到新的位置
+    pub fn move_layer(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.layers.len() || to >= self.layers.len() {
+            return false;
+        }
+        let layer = self.layers.remove(from);
+        self.layers.insert(to, layer);
+        true
+    }
+
+    /// 获取图层 (可变)
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer> {
+        self.layers.get_mut(index)
+    }
+
+    /// 是否包含几何变换
+    pub fn has_geometry(&self) -> bool {
+        self.operations.iter().any(|op| !op.is_identity())
+    }
+
+    /// 堆上额外占用的内存 (字节)，用于估算历史记录的内存
+    pub fn heap_size(&self) -> usize {
+        self.operations.capacity() * std::mem::size_of::<EditOperation>()
+            + self.masks.capacity() * std::mem::size_of::<Mask>()
+            + self.masks.iter().map(Mask::heap_size).sum::<usize>()
+            + self.layers.capacity() * std::mem::size_of::<Layer>()
+            + self.layers.iter().map(Layer::heap_size).sum::<usize>()
+    }
+}
@@ -12,9 +12,86 @@ struct AdjustmentParams {
     _padding: f32,
 }
 
+struct ColorWheel {
+    hue: f32,          // 0.0 to 360.0
+    saturation: f32,   // 0.0 to 1.0
+    luminance: f32,    // -1.0 to 1.0
+    _padding: f32,
+}
+
+struct ColorGrading {
+    shadows: ColorWheel,
+    midtones: ColorWheel,
+    highlights: ColorWheel,
+    global: ColorWheel,
+    blending: f32,     // 0.0 to 1.0
+    balance: f32,      // -1.0 to 1.0
+    _padding: vec2<f32>,
+}
+
+// Linear working space to display space conversion
+struct OutputTransform {
+    matrix: mat3x3<f32>,
+}
+
+// Output UV to source UV mapping of the geometry operation stack, a homography
+// when perspective corrections are present (divide by the third component)
+struct GeometryTransform {
+    matrix: mat3x3<f32>,
+}
+
+// Before/after comparison
+struct Comparison {
+    mode: u32,         // 0 edited, 1 split, 2 side by side, 3 original
+    orientation: u32,  // 0 vertical divider, 1 horizontal divider
+    position: f32,     // divider position, 0.0 to 1.0
+    divider: u32,      // 1 to draw the divider line
+}
+
+// Local adjustment mask in normalized source coordinates
+struct Mask {
+    kind: u32,         // 0 linear, 1 radial, 2 brush, 3 whole image
+    invert: u32,
+    feather: f32,
+    angle: f32,        // linear direction or radial rotation, degrees
+    center: vec2<f32>,
+    radius: vec2<f32>, // radial only
+    channel: u32,      // brush only, channel of the brush mask texture
+    range_kind: u32,   // 0 none, 1 luminance, 2 color
+    range_smoothness: f32,
+    _padding: u32,
+    range: vec4<f32>,  // luminance (low, high, -, -) or color (r, g, b, tolerance)
+    adjustments: AdjustmentParams,
+}
+
+struct Masks {
+    count: u32,
+    aspect: f32,       // source width / height
+    _padding: vec2<f32>,
+    items: array<Mask, 8>,
+}
+
 @group(0) @binding(0) var texture: texture_2d<f32>;
-@group(0) @binding(1) var sampler: sampler;
+@group(0) @binding(1) var image_sampler: sampler;
 @group(0) @binding(2) var<uniform> params: AdjustmentParams;
+@group(0) @binding(3) var<uniform> grading: ColorGrading;
+@group(0) @binding(4) var<uniform> output: OutputTransform;
+@group(0) @binding(5) var<uniform> geometry: GeometryTransform;
+@group(0) @binding(6) var<uniform> comparison: Comparison;
+@group(0) @binding(7) var<uniform> masks: Masks;
+// Rasterized brush masks in source space, one mask per channel
+@group(0) @binding(8) var brush_texture: texture_2d<f32>;
+
+const COMPARE_SPLIT: u32 = 1u;
+const COMPARE_SIDE_BY_SIDE: u32 = 2u;
+const COMPARE_ORIGINAL: u32 = 3u;
+const DIVIDER_COLOR: vec3<f32> = vec3<f32>(1.0, 1.0, 1.0);
+
+// Maximum source taps per axis when one output pixel covers several texels
+const MAX_TAPS: i32 = 4;
+
+const LUMA: vec3<f32> = vec3<f32>(0.299, 0.587, 0.114);
+const PERCEPTUAL_GAMMA: f32 = 1.0 / 2.2;
 
 @vertex
 fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
@@ -34,19 +111,174 @@ fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
     return out;
 }
 
+// Average taps spread over the pixel footprint in the source, so the
+// compressed side of a perspective correction does not alias. Matches the CPU
+// resampler: alpha-weighted color, one tap when the footprint is a texel or less
+fn sample_source(uv: vec2<f32>, dx: vec2<f32>, dy: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(texture));
+    let taps_x = clamp(i32(ceil(length(dx * size) - 0.01)), 1, MAX_TAPS);
+    let taps_y = clamp(i32(ceil(length(dy * size) - 0.01)), 1, MAX_TAPS);
+    var sum = vec4<f32>(0.0);
+    for (var j = 0; j < taps_y; j++) {
+        for (var i = 0; i < taps_x; i++) {
+            let offset_x = (f32(i) + 0.5) / f32(taps_x) - 0.5;
+            let offset_y = (f32(j) + 0.5) / f32(taps_y) - 0.5;
+            let tap = textureSampleLevel(
+                texture, image_sampler, uv + dx * offset_x + dy * offset_y, 0.0
+            );
+            sum += vec4<f32>(tap.rgb * tap.a, tap.a);
+        }
+    }
+    let count = f32(taps_x * taps_y);
+    return vec4<f32>(sum.rgb / max(sum.a, 1e-6), sum.a / count);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let color = textureSample(texture, sampler, in.uv);
+    // Coordinate across the divider and its size in pixels
+    let horizontal = comparison.orientation == 1u;
+    let along = select(in.uv.x, in.uv.y, horizontal);
+    let pixel = select(fwidth(in.uv.x), fwidth(in.uv.y), horizontal);
+    let side_by_side = comparison.mode == COMPARE_SIDE_BY_SIDE;
+    let divider_at = select(comparison.position, 0.5, side_by_side);
+    let before_side = along < divider_at;
+
+    // Side by side shows the whole image in each half
+    let half = select(along * 2.0, along * 2.0 - 1.0, !before_side);
+    let view_uv = select(
+        in.uv,
+        select(vec2<f32>(half, in.uv.y), vec2<f32>(in.uv.x, half), horizontal),
+        side_by_side
+    );
+    let show_original = comparison.mode == COMPARE_ORIGINAL
+        || ((comparison.mode == COMPARE_SPLIT || side_by_side) && before_side);
+
+    // Sample the untouched source through the composed geometry transform. The
+    // full-screen quad cannot carry a perspective warp, so the homography is
+    // evaluated per fragment, which keeps the mapping exact in every view mode
+    let projected = geometry.matrix * vec3<f32>(view_uv, 1.0);
+    let source_uv = projected.xy / max(projected.z, 1e-6);
+    let sampled = sample_source(source_uv, dpdx(source_uv), dpdy(source_uv));
+    let inside = projected.z > 0.0
+        && all(source_uv >= vec2<f32>(0.0)) && all(source_uv <= vec2<f32>(1.0));
+    let color = select(vec4<f32>(0.0), sampled, inside);
 
-    // Apply brightness
-    let with_brightness = color.rgb + params.brightness;
+    // Apply brightness, contrast and saturation
+    let adjusted = apply_basic(color.rgb, params);
+
+    // Apply color grading
+    let global = apply_color_grading(adjusted);
+
+    // Apply local adjustments on top, weighted by each mask
+    let brush = textureSample(brush_texture, image_sampler, source_uv);
+    var graded = global;
+    for (var i = 0u; i < masks.count; i++) {
+        let mask = masks.items[i];
+        let weight = mask_weight(mask, source_uv, brush, color.rgb);
+        graded = mix(graded, apply_basic(graded, mask.adjustments), weight);
+    }
+
+    // The original keeps the geometry so both sides line up
+    let shown = select(graded, color.rgb, show_original);
+
+    // Convert from the working space to the display space
+    let display = output.matrix * shown;
+
+    // Divider line, about two pixels wide
+    let compare = comparison.mode == COMPARE_SPLIT || side_by_side;
+    let on_divider = compare && comparison.divider == 1u && abs(along - divider_at) <= pixel;
+    let final_rgb = select(display, DIVIDER_COLOR, on_divider);
+    let alpha = select(color.a, 1.0, on_divider);
+
+    return vec4<f32>(final_rgb, alpha);
+}
+
+fn apply_basic(rgb: vec3<f32>, adjustments: AdjustmentParams) -> vec3<f32> {
+    // Brightness
+    let with_brightness = rgb + adjustments.brightness;
+
+    // Contrast
+    let with_contrast = (with_brightness - 0.5) * adjustments.contrast + 0.5;
+
+    // Saturation (convert to grayscale and mix)
+    let gray = dot(with_contrast, LUMA);
+    return mix(vec3<f32>(gray), with_contrast, adjustments.saturation);
+}
+
+// Mask weight at a source UV, distances measured in units of the source height
+fn mask_weight(mask: Mask, uv: vec2<f32>, brush: vec4<f32>, rgb: vec3<f32>) -> f32 {
+    let angle = radians(mask.angle);
+    let delta = (uv - mask.center) * vec2<f32>(masks.aspect, 1.0);
+
+    var weight: f32;
+    if mask.kind == 3u {
+        weight = 1.0;
+    } else if mask.kind == 2u {
+        weight = brush[mask.channel];
+    } else if mask.kind == 0u {
+        let distance = delta.x * cos(angle) + delta.y * sin(angle);
+        let half = mask.feather * 0.5;
+        weight = select(smoothstep(-half, half, distance), step(0.0, distance), half <= 0.0);
+    } else {
+        let local = vec2<f32>(
+            delta.x * cos(angle) + delta.y * sin(angle),
+            -delta.x * sin(angle) + delta.y * cos(angle)
+        );
+        let r = length(local / (mask.radius * vec2<f32>(masks.aspect, 1.0)));
+        let edge = select(smoothstep(1.0 - mask.feather, 1.0, r), step(1.0, r), mask.feather <= 0.0);
+        weight = 1.0 - edge;
+    }
+    let shape = select(weight, 1.0 - weight, mask.invert == 1u);
+    return shape * range_weight(mask, rgb);
+}
+
+// smoothstep that degrades to a step when both edges meet
+fn soft_step(edge0: f32, edge1: f32, x: f32) -> f32 {
+    return select(smoothstep(edge0, edge1, x), step(edge0, x), edge1 <= edge0);
+}
+
+// Luminance or color range selection of the untouched source pixel
+fn range_weight(mask: Mask, rgb: vec3<f32>) -> f32 {
+    let smoothness = mask.range_smoothness;
+    if mask.range_kind == 1u {
+        let lightness = pow(clamp(dot(rgb, LUMA), 0.0, 1.0), PERCEPTUAL_GAMMA);
+        return soft_step(mask.range.x - smoothness, mask.range.x, lightness)
+            * (1.0 - soft_step(mask.range.y, mask.range.y + smoothness, lightness));
+    } else if mask.range_kind == 2u {
+        let encoded = pow(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(PERCEPTUAL_GAMMA));
+        let target_color = pow(mask.range.rgb, vec3<f32>(PERCEPTUAL_GAMMA));
+        let distance = length(encoded - target_color) / sqrt(3.0);
+        return 1.0 - soft_step(mask.range.w, mask.range.w + smoothness, distance);
+    }
+    return 1.0;
+}
+
+// Pure hue color (full saturation and value) for an angle in degrees
+fn hue_to_rgb(hue: f32) -> vec3<f32> {
+    let h = fract(hue / 360.0 + vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0));
+    return clamp(abs(h * 6.0 - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Color offset of a wheel, with the hue tint normalized to zero luminance
+fn wheel_offset(wheel: ColorWheel) -> vec3<f32> {
+    let hue_color = hue_to_rgb(wheel.hue);
+    let tint = hue_color - dot(hue_color, LUMA);
+    return tint * wheel.saturation * 0.5 + wheel.luminance * 0.5;
+}
 
-    // Apply contrast
-    let with_contrast = (with_brightness - 0.5) * params.contrast + 0.5;
+fn apply_color_grading(rgb: vec3<f32>) -> vec3<f32> {
+    let luma = clamp(dot(rgb, LUMA), 0.0, 1.0);
 
-    // Apply saturation (convert to grayscale and mix)
-    let gray = dot(with_contrast, vec3<f32>(0.299, 0.587, 0.114));
-    let with_saturation = mix(gray, with_contrast, params.saturation);
+    // Balance moves the shadow/highlight pivot, blending widens the ranges
+    let pivot = 0.5 - grading.balance * 0.4;
+    let falloff = exp2(1.0 - 2.0 * grading.blending);
+    let shadows = pow(1.0 - smoothstep(0.0, pivot, luma), falloff);
+    let highlights = pow(smoothstep(pivot, 1.0, luma), falloff);
+    let midtones = 1.0 - shadows - highlights;
 
-    return vec4<f32>(with_saturation, color.a);
+    return rgb
+        + wheel_offset(grading.shadows) * shadows
+        + wheel_offset(grading.midtones) * midtones
+        + wheel_offset(grading.highlights) * highlights
+        + wheel_offset(grading.global);
 }
@@ -2,20 +2,47 @@
 
 use crate::pipelines::RenderPipelines;
 use crate::texture::TextureManager;
-use editor_studio_params::AdjustmentParams;
+use editor_studio_math::{
+    constrain_crop, largest_crop, max_crop_scale, solve_crop_drag, swap_crop_orientation,
+    CropDragOptions, CropHandle, CropSuggester, GeometryPlan, Homography, ImageSize, MaskRaster,
+    RatioSuggestions, Subject,
+};
+use editor_studio_params::{
+    AdjustField, AdjustmentClipboard, AdjustmentParams, BlendMode, BrushStroke, ColorGrading,
+    ColorSpace, ComparisonMode, ComparisonState, CropRect, CropTransform, EditCommand, EditHistory,
+    EditOperation, EditState, Layer, LayerTransform, Mask, MaskShape, PasteGroups, Preset,
+    RangeSelection, SplitOrientation, ToneRange, VirtualCopy,
+};
 use wasm_bindgen::prelude::*;
 
 mod pipelines;
 mod shaders;
 mod texture;
 
+/// 纹理的最大边长 (像素)，更大的原图需要以代理图像上传
+pub const MAX_TEXTURE_DIMENSION: u32 = 8192;
+
+/// 初始副本的名称
+pub const ORIGINAL_COPY_NAME: &str = "Original";
+
 /// wgpu 渲染器
 pub struct Renderer {
     device: wgpu::Device,
     queue: wgpu::Queue,
     texture_manager: TextureManager,
     pipelines: RenderPipelines,
-    params: AdjustmentParams,
+    /// 共享同一原图的虚拟副本，至少有一个
+    copies: Vec<VirtualCopy>,
+    active_copy: usize,
+    change_listener: Option<Box<dyn FnMut()>>,
+    /// 原图尺寸 (上传的纹理可能是缩小的代理)
+    source_size: Option<ImageSize>,
+    source_color_space: ColorSpace,
+    display_color_space: ColorSpace,
+    comparison: ComparisonState,
+    /// 当前画笔蒙版纹理对应的笔触和分辨率
+    rasterized_brushes: Vec<MaskShape>,
+    rasterized_size: (u32, u32),
     render_requested: bool,
 }
 
@@ -45,7 +72,7 @@ impl Renderer {
                     label: Some("GPU Device"),
                     required_features: wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
                     required_limits: wgpu::Limits {
-                        max_texture_dimension_2d: 8192,
+                        max_texture_dimension_2d: MAX_TEXTURE_DIMENSION,
                         ..Default::default()
                     },
                 },
@@ -62,7 +89,15 @@ impl Renderer {
             queue,
             texture_manager,
             pipelines,
-            params: AdjustmentParams::default(),
+            copies: vec![VirtualCopy::new(ORIGINAL_COPY_NAME)],
+            active_copy: 0,
+            change_listener: None,
+            source_size: None,
+            source_color_space: ColorSpace::WORKING,
+            display_color_space: ColorSpace::Srgb,
+            comparison: ComparisonState::default(),
+            rasterized_brushes: Vec::new(),
+            rasterized_size: (0, 0),
             render_requested: false,
         })
     }
@@ -87,33 +122,706 @@ impl Renderer {
         &mut self.texture_manager
     }
 
+    /// 上传源图像
+    ///
+    /// # 参数
+    /// * `data` - RGBA8 像素数据
+    /// * `color_space` - 像素数据所在的色彩空间
+    pub fn load_image(
+        &mut self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        color_space: ColorSpace,
+    ) -> Result<(), JsValue> {
+        self.load_proxy(
+            data,
+            width,
+            height,
+            ImageSize::new(width, height),
+            color_space,
+        )
+    }
+
+    /// 上传源图像的预览代理
+    ///
+    /// 交互渲染使用代理纹理，编辑状态与分辨率无关，
+    /// 尺寸相关的查询 (如 [`Renderer::image_size`]) 仍然返回原图尺寸。
+    ///
+    /// # 参数
+    /// * `data` - 代理图像的 RGBA8 像素数据
+    /// * `source_size` - 原图尺寸
+    /// * `color_space` - 像素数据所在的色彩空间
+    pub fn load_proxy(
+        &mut self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        source_size: ImageSize,
+        color_space: ColorSpace,
+    ) -> Result<(), JsValue> {
+        if width > MAX_TEXTURE_DIMENSION || height > MAX_TEXTURE_DIMENSION {
+            return Err(JsValue::from_str(&format!(
+                "Image {}x{} exceeds the maximum texture size {}",
+                width, height, MAX_TEXTURE_DIMENSION
+            )));
+        }
+        self.texture_manager
+            .load_image(&self.device, &self.queue, data, width, height)?;
+        self.source_size = Some(source_size);
+        self.source_color_space = color_space;
+        self.render_requested = true;
+        Ok(())
+    }
+
+    /// 设置显示器的色彩空间
+    pub fn set_display_color_space(&mut self, color_space: ColorSpace) {
+        self.display_color_space = color_space;
+        self.render_requested = true;
+    }
+
+    /// 获取显示器的色彩空间
+    pub fn display_color_space(&self) -> ColorSpace {
+        self.display_color_space
+    }
+
+    /// 设置前后对比模式
+    pub fn set_comparison_mode(&mut self, mode: ComparisonMode) {
+        self.comparison.mode = mode;
+        self.comparison.holding = false;
+        self.render_requested = true;
+    }
+
+    /// 设置分割线方向和位置 (0.0 到 1.0)
+    pub fn set_comparison_split(&mut self, orientation: SplitOrientation, position: f32) {
+        self.comparison.orientation = orientation;
+        self.comparison.set_position(position);
+        self.render_requested = true;
+    }
+
+    /// 设置是否绘制分割线
+    pub fn set_comparison_divider(&mut self, visible: bool) {
+        self.comparison.show_divider = visible;
+        self.render_requested = true;
+    }
+
+    /// 按住/松开对比 (按住对比模式)
+    pub fn set_comparison_holding(&mut self, holding: bool) {
+        self.comparison.holding = holding;
+        self.render_requested = true;
+    }
+
+    /// 获取前后对比设置
+    pub fn comparison(&self) -> &ComparisonState {
+        &self.comparison
+    }
+
     /// 设置亮度
     pub fn set_brightness(&mut self, value: f32) {
-        self.params.set_brightness(value);
-        self.render_requested = true;
+        self.edit(EditCommand::Adjust(AdjustField::Brightness), |state| {
+            state.adjustments.set_brightness(value)
+        });
     }
 
     /// 设置对比度
     pub fn set_contrast(&mut self, value: f32) {
-        self.params.set_contrast(value);
-        self.render_requested = true;
+        self.edit(EditCommand::Adjust(AdjustField::Contrast), |state| {
+            state.adjustments.set_contrast(value)
+        });
     }
 
     /// 设置饱和度
     pub fn set_saturation(&mut self, value: f32) {
-        self.params.set_saturation(value);
-        self.render_requested = true;
+        self.edit(EditCommand::Adjust(AdjustField::Saturation), |state| {
+            state.adjustments.set_saturation(value)
+        });
+    }
+
+    /// 设置色彩分级色轮
+    pub fn set_color_wheel(&mut self, range: ToneRange, hue: f32, saturation: f32, luminance: f32) {
+        self.edit(
+            EditCommand::Adjust(AdjustField::ColorWheel(range)),
+            |state| state.grading.set_wheel(range, hue, saturation, luminance),
+        );
+    }
+
+    /// 设置色彩分级混合程度
+    pub fn set_grading_blending(&mut self, value: f32) {
+        self.edit(EditCommand::Adjust(AdjustField::GradingBlending), |state| {
+            state.grading.set_blending(value)
+        });
+    }
+
+    /// 设置色彩分级平衡
+    pub fn set_grading_balance(&mut self, value: f32) {
+        self.edit(EditCommand::Adjust(AdjustField::GradingBalance), |state| {
+            state.grading.set_balance(value)
+        });
     }
 
     /// 重置所有调整
     pub fn reset_adjustments(&mut self) {
-        self.params.reset();
-        self.render_requested = true;
+        self.edit(EditCommand::Reset, |state| {
+            state.adjustments.reset();
+            state.grading.reset();
+        });
     }
 
     /// 获取当前调整参数
     pub fn params(&self) -> &AdjustmentParams {
-        &self.params
+        &self.copy().state.adjustments
+    }
+
+    /// 获取当前色彩分级参数
+    pub fn grading(&self) -> &ColorGrading {
+        &self.copy().state.grading
+    }
+
+    /// 设置裁剪区域
+    pub fn set_crop(&mut self, crop: Option<CropRect>) {
+        self.edit(EditCommand::Crop, |state| state.set_crop(crop));
+    }
+
+    /// 获取当前的裁剪区域
+    pub fn crop(&self) -> Option<CropRect> {
+        self.copy().state.crop()
+    }
+
+    /// 拖动裁剪框手柄，返回新的裁剪区域
+    ///
+    /// # 参数
+    /// * `start` - 拖动开始时的裁剪区域
+    /// * `dx`, `dy` - 从拖动开始累计的位移 (归一化坐标)
+    pub fn drag_crop_handle(
+        &mut self,
+        start: &CropRect,
+        handle: CropHandle,
+        dx: f32,
+        dy: f32,
+        options: &CropDragOptions,
+    ) -> Option<CropRect> {
+        let size = self.crop_size()?;
+        let crop = solve_crop_drag(start, handle, dx, dy, options, &size)?;
+        self.set_crop(Some(crop));
+        Some(crop)
+    }
+
+    /// 互换裁剪区域宽高比的横竖方向 (如 3:2 变为 2:3)，保持中心和面积
+    pub fn swap_crop_orientation(&mut self) -> Option<CropRect> {
+        let size = self.crop_size()?;
+        let crop = self.crop()?;
+        let crop = swap_crop_orientation(&crop, crop.ratio, &size);
+        self.set_crop(Some(crop));
+        Some(crop)
+    }
+
+    /// 生成裁剪建议，坐标相对于裁剪操作的输入图像
+    ///
+    /// # 参数
+    /// * `subjects` - 主体 (归一化坐标)
+    /// * `ratios` - 目标像素宽高比
+    pub fn suggest_crops(
+        &self,
+        suggester: &CropSuggester,
+        subjects: &[Subject],
+        ratios: &[f32],
+    ) -> Option<Vec<RatioSuggestions>> {
+        let size = self.crop_size()?;
+        Some(suggester.suggest(&size, subjects, ratios))
+    }
+
+    /// 获取当前的拉直裁剪
+    pub fn crop_transform(&self) -> Option<CropTransform> {
+        self.copy().state.crop_transform()
+    }
+
+    /// 设置拉直裁剪，裁剪区域被限制在图像内 (保持角度和宽高比)，包含非有限值时忽略
+    pub fn set_crop_transform(&mut self, crop: Option<CropTransform>) {
+        let crop = match (crop, self.crop_input_size()) {
+            (Some(crop), Some(size)) => match constrain_crop(&crop, &size) {
+                Some(crop) => Some(crop),
+                None => return,
+            },
+            (crop, _) => crop,
+        };
+        self.edit(EditCommand::Crop, |state| state.set_crop_transform(crop));
+    }
+
+    /// 按角度拉直，使用该角度下能放进图像的最大裁剪区域
+    ///
+    /// 保持当前拉直裁剪的像素宽高比，没有时使用图像的宽高比。
+    pub fn set_straighten(&mut self, angle: f32) {
+        let Some(size) = self.crop_input_size() else {
+            return;
+        };
+        let ratio = self.crop_transform().map(|crop| {
+            crop.rect
+                .ratio
+                .unwrap_or_else(|| size.crop_ratio(&crop.rect))
+        });
+        let crop = largest_crop(&size, angle, ratio);
+        self.edit(EditCommand::Crop, |state| {
+            state.set_crop_transform(Some(crop))
+        });
+    }
+
+    /// 当前拉直裁剪绕中心允许的最大缩放倍数
+    pub fn max_crop_scale(&self) -> Option<f32> {
+        let crop = self.crop_transform()?;
+        Some(max_crop_scale(&crop, &self.crop_input_size()?))
+    }
+
+    /// 设置旋转角度
+    pub fn set_rotation(&mut self, degrees: f32) {
+        self.edit(EditCommand::Rotate, |state| state.set_rotation(degrees));
+    }
+
+    /// 设置翻转
+    pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+        self.edit(EditCommand::Flip, |state| {
+            state.set_flip(horizontal, vertical)
+        });
+    }
+
+    /// 获取当前的四点透视校正角点
+    pub fn perspective(&self) -> Option<[[f32; 2]; 4]> {
+        self.copy().state.perspective()
+    }
+
+    /// 设置四点透视校正 (左上、右上、右下、左下，归一化坐标)，`None` 清除
+    pub fn set_perspective(&mut self, corners: Option<[[f32; 2]; 4]>) {
+        self.edit(EditCommand::Perspective, |state| {
+            state.set_perspective(corners)
+        });
+    }
+
+    /// 获取当前的梯形校正 (竖直, 水平)
+    pub fn keystone(&self) -> (f32, f32) {
+        self.copy().state.keystone()
+    }
+
+    /// 设置梯形校正 (-1.0 到 1.0)
+    pub fn set_keystone(&mut self, vertical: f32, horizontal: f32) {
+        self.edit(EditCommand::Perspective, |state| {
+            state.set_keystone(vertical, horizontal)
+        });
+    }
+
+    /// 获取几何操作栈
+    pub fn operations(&self) -> &[EditOperation] {
+        &self.copy().state.operations
+    }
+
+    /// 删除指定位置的几何操作，其余操作从原图重新计算
+    pub fn remove_operation(&mut self, index: usize) -> bool {
+        let mut removed = false;
+        self.edit(EditCommand::RemoveOperation, |state| {
+            removed = state.remove_operation(index).is_some()
+        });
+        removed
+    }
+
+    /// 移动几何操作到新的位置
+    pub fn move_operation(&mut self, from: usize, to: usize) -> bool {
+        let mut moved = false;
+        self.edit(EditCommand::MoveOperation, |state| {
+            moved = state.move_operation(from, to)
+        });
+        moved
+    }
+
+    /// 获取局部调整蒙版
+    pub fn masks(&self) -> &[Mask] {
+        &self.copy().state.masks
+    }
+
+    /// 添加蒙版，返回其序号；达到数量上限时返回 None
+    pub fn add_mask(&mut self, mask: Mask) -> Option<usize> {
+        let mut index = None;
+        self.edit(EditCommand::AddMask, |state| index = state.add_mask(mask));
+        index
+    }
+
+    /// 修改蒙版形状 (拖动时连续的修改合并为一步)
+    pub fn set_mask_shape(&mut self, index: usize, shape: MaskShape) -> bool {
+        self.edit_mask(index, |mask| mask.set_shape(shape))
+    }
+
+    /// 设置蒙版是否反转
+    pub fn set_mask_invert(&mut self, index: usize, invert: bool) -> bool {
+        self.edit_mask(index, |mask| mask.invert = invert)
+    }
+
+    /// 设置蒙版的亮度/颜色范围选择，None 表示只按形状选择
+    pub fn set_mask_range(&mut self, index: usize, range: Option<RangeSelection>) -> bool {
+        self.edit_mask(index, |mask| mask.range = range)
+    }
+
+    /// 设置蒙版区域内的调整参数
+    pub fn set_mask_adjustments(&mut self, index: usize, adjustments: AdjustmentParams) -> bool {
+        self.edit_mask(index, |mask| mask.adjustments = adjustments)
+    }
+
+    /// 给画笔蒙版添加一个笔触 (每个笔触单独撤销)
+    pub fn add_brush_stroke(&mut self, index: usize, stroke: BrushStroke) -> bool {
+        let mut added = false;
+        self.edit(EditCommand::BrushStroke, |state| {
+            added = state.add_brush_stroke(index, stroke)
+        });
+        added
+    }
+
+    /// 删除蒙版
+    pub fn remove_mask(&mut self, index: usize) -> bool {
+        let mut removed = false;
+        self.edit(EditCommand::RemoveMask, |state| {
+            removed = state.remove_mask(index).is_some()
+        });
+        removed
+    }
+
+    /// 上传图层图像 (RGBA8，与原图处于同一色彩空间)
+    ///
+    /// 图层按编号引用图像，同一图像可以被多个图层和虚拟副本共享。
+    pub fn load_layer_image(
+        &mut self,
+        image: u32,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<(), JsValue> {
+        if width > MAX_TEXTURE_DIMENSION || height > MAX_TEXTURE_DIMENSION {
+            return Err(JsValue::from_str(&format!(
+                "Layer image {}x{} exceeds the maximum texture size {}",
+                width, height, MAX_TEXTURE_DIMENSION
+            )));
+        }
+        self.texture_manager.load_layer_image(
+            &self.device,
+            &self.queue,
+            image,
+            data,
+            width,
+            height,
+        );
+        self.render_requested = true;
+        Ok(())
+    }
+
+    /// 获取图层 (从下到上)
+    pub fn layers(&self) -> &[Layer] {
+        &self.copy().state.layers
+    }
+
+    /// 添加图层到最上面，返回其序号；达到数量上限时返回 None
+    pub fn add_layer(&mut self, layer: Layer) -> Option<usize> {
+        let mut index = None;
+        self.edit(EditCommand::AddLayer, |state| {
+            index = state.add_layer(layer)
+        });
+        index
+    }
+
+    /// 修改图层位置 (拖动时连续的修改合并为一步)
+    pub fn set_layer_transform(&mut self, index: usize, transform: LayerTransform) -> bool {
+        self.edit_layer(index, |layer| layer.transform = transform.clamped())
+    }
+
+    /// 设置图层不透明度 (0.0 到 1.0)
+    pub fn set_layer_opacity(&mut self, index: usize, opacity: f32) -> bool {
+        self.edit_layer(index, |layer| layer.set_opacity(opacity))
+    }
+
+    /// 设置图层混合模式
+    pub fn set_layer_blend_mode(&mut self, index: usize, blend_mode: BlendMode) -> bool {
+        self.edit_layer(index, |layer| layer.blend_mode = blend_mode)
+    }
+
+    /// 显示或隐藏图层
+    pub fn set_layer_visible(&mut self, index: usize, visible: bool) -> bool {
+        self.edit_layer(index, |layer| layer.visible = visible)
+    }
+
+    /// 删除图层 (图层图像保留，撤销后仍可使用)
+    pub fn remove_layer(&mut self, index: usize) -> bool {
+        let mut removed = false;
+        self.edit(EditCommand::RemoveLayer, |state| {
+            removed = state.remove_layer(index).is_some()
+        });
+        removed
+    }
+
+    /// 调整图层顺序
+    pub fn move_layer(&mut self, from: usize, to: usize) -> bool {
+        let mut moved = false;
+        self.edit(EditCommand::MoveLayer, |state| {
+            moved = state.move_layer(from, to)
+        });
+        moved
+    }
+
+    /// 按强度应用预设 (0.0 到 1.0)
+    pub fn apply_preset(&mut self, preset: &Preset, intensity: f32) {
+        self.edit(EditCommand::ApplyPreset(preset.name.clone()), |state| {
+            preset.apply(state, intensity)
+        });
+    }
+
+    /// 获取原图尺寸
+    pub fn image_size(&self) -> Option<(u32, u32)> {
+        self.source_size.map(|size| (size.width, size.height))
+    }
+
+    /// 预览纹理相对原图的缩放比例 (不超过 1.0)
+    pub fn preview_scale(&self) -> Option<f32> {
+        let size = self.source_size?;
+        let texture = self.texture_manager.source_texture()?;
+        Some(texture.width() as f32 / size.width as f32)
+    }
+
+    /// 当前几何操作栈的变换计划 (未加载图像时返回 None)
+    pub fn geometry_plan(&self) -> Option<GeometryPlan> {
+        self.plan_for(&self.copy().state)
+    }
+
+    /// 应用几何操作后的输出尺寸
+    pub fn output_size(&self) -> Option<(u32, u32)> {
+        self.geometry_plan()
+            .map(|plan| (plan.output.width, plan.output.height))
+    }
+
+    /// 复制当前的调整参数 (未加载图像时返回 None)
+    pub fn copy_adjustments(&self) -> Option<AdjustmentClipboard> {
+        let (width, height) = self.image_size()?;
+        Some(AdjustmentClipboard::copy(
+            &self.copy().state,
+            width as f32 / height as f32,
+        ))
+    }
+
+    /// 粘贴调整参数 (未加载图像时忽略)
+    pub fn paste_adjustments(&mut self, clipboard: &AdjustmentClipboard, groups: PasteGroups) {
+        if let Some((width, height)) = self.image_size() {
+            let aspect = width as f32 / height as f32;
+            self.edit(EditCommand::Paste, |state| {
+                clipboard.paste(state, groups, aspect)
+            });
+        }
+    }
+
+    /// 获取完整的编辑状态
+    pub fn state(&self) -> &EditState {
+        &self.copy().state
+    }
+
+    /// 替换完整的编辑状态 (如应用配方)，状态先被限制到有效范围
+    pub fn set_state(&mut self, mut state: EditState) {
+        state.sanitize();
+        self.edit(EditCommand::ApplyRecipe, |current| *current = state);
+    }
+
+    /// 结束当前的连续编辑 (如滑块拖动结束)，之后的修改记录为新的一步
+    pub fn end_edit(&mut self) {
+        self.copy_mut().history.seal();
+    }
+
+    /// 撤销一步
+    pub fn undo(&mut self) -> bool {
+        let copy = self.copy_mut();
+        let undone = copy.history.undo(&mut copy.state).is_some();
+        if undone {
+            self.changed();
+        }
+        undone
+    }
+
+    /// 重做一步
+    pub fn redo(&mut self) -> bool {
+        let copy = self.copy_mut();
+        let redone = copy.history.redo(&mut copy.state).is_some();
+        if redone {
+            self.changed();
+        }
+        redone
+    }
+
+    /// 是否可以撤销
+    pub fn can_undo(&self) -> bool {
+        self.copy().history.can_undo()
+    }
+
+    /// 是否可以重做
+    pub fn can_redo(&self) -> bool {
+        self.copy().history.can_redo()
+    }
+
+    /// 获取历史记录
+    pub fn history(&self) -> &EditHistory {
+        &self.copy().history
+    }
+
+    /// 获取历史记录 (可变)
+    pub fn history_mut(&mut self) -> &mut EditHistory {
+        &mut self.copy_mut().history
+    }
+
+    /// 所有虚拟副本
+    pub fn copies(&self) -> &[VirtualCopy] {
+        &self.copies
+    }
+
+    /// 当前编辑的虚拟副本序号
+    pub fn active_copy(&self) -> usize {
+        self.active_copy
+    }
+
+    /// 以当前编辑状态创建虚拟副本并切换到新副本，返回其序号
+    pub fn create_virtual_copy(&mut self, name: &str) -> usize {
+        let copy = VirtualCopy::from_state(name, self.copy().state.clone());
+        self.copies.push(copy);
+        self.active_copy = self.copies.len() - 1;
+        self.changed();
+        self.active_copy
+    }
+
+    /// 切换到指定的虚拟副本
+    pub fn select_copy(&mut self, index: usize) -> bool {
+        if index >= self.copies.len() {
+            return false;
+        }
+        if index != self.active_copy {
+            self.copy_mut().history.seal();
+            self.active_copy = index;
+            self.changed();
+        }
+        true
+    }
+
+    /// 删除虚拟副本 (最后一个副本不能删除)
+    pub fn remove_copy(&mut self, index: usize) -> bool {
+        if index >= self.copies.len() || self.copies.len() == 1 {
+            return false;
+        }
+        self.copies.remove(index);
+        if self.active_copy == index {
+            self.active_copy = index.min(self.copies.len() - 1);
+            self.changed();
+        } else if self.active_copy > index {
+            self.active_copy -= 1;
+        }
+        true
+    }
+
+    /// 重命名虚拟副本
+    pub fn rename_copy(&mut self, index: usize, name: &str) -> bool {
+        match self.copies.get_mut(index) {
+            Some(copy) => {
+                copy.name = name.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 保存当前副本的编辑状态为快照
+    pub fn take_snapshot(&mut self, name: &str) {
+        self.copy_mut().take_snapshot(name);
+    }
+
+    /// 恢复当前副本的快照 (可撤销)
+    pub fn restore_snapshot(&mut self, name: &str) -> bool {
+        let Some(snapshot) = self.copy().snapshot(name).cloned() else {
+            return false;
+        };
+        self.edit(EditCommand::RestoreSnapshot(snapshot.name), |state| {
+            *state = snapshot.state
+        });
+        true
+    }
+
+    /// 删除当前副本的快照
+    pub fn remove_snapshot(&mut self, name: &str) -> bool {
+        self.copy_mut().remove_snapshot(name).is_some()
+    }
+
+    /// 设置编辑状态变化 (编辑、撤销、重做) 时的回调
+    pub fn set_change_listener(&mut self, listener: Option<Box<dyn FnMut()>>) {
+        self.change_listener = listener;
+    }
+
+    /// 修改编辑状态并记录到历史
+    fn edit(&mut self, command: EditCommand, apply: impl FnOnce(&mut EditState)) {
+        let copy = self.copy_mut();
+        let before = copy.state.clone();
+        apply(&mut copy.state);
+        if before != copy.state {
+            copy.history.record(command, before, &copy.state);
+            self.changed();
+        }
+    }
+
+    /// 裁剪操作的输入图像尺寸 (之前所有几何操作的输出)
+    fn crop_size(&self) -> Option<ImageSize> {
+        let (width, height) = self.image_size()?;
+        let state = &self.copy().state;
+        let preceding = &state.operations[..state.crop_index()];
+        Some(GeometryPlan::new(&ImageSize::new(width, height), preceding).output)
+    }
+
+    /// 拉直裁剪操作的输入图像尺寸 (之前所有几何操作的输出)
+    fn crop_input_size(&self) -> Option<ImageSize> {
+        let (width, height) = self.image_size()?;
+        let state = &self.copy().state;
+        let preceding = &state.operations[..state.crop_transform_index()];
+        Some(GeometryPlan::new(&ImageSize::new(width, height), preceding).output)
+    }
+
+    fn plan_for(&self, state: &EditState) -> Option<GeometryPlan> {
+        let (width, height) = self.image_size()?;
+        Some(GeometryPlan::new(
+            &ImageSize::new(width, height),
+            &state.operations,
+        ))
+    }
+
+    fn copy(&self) -> &VirtualCopy {
+        &self.copies[self.active_copy]
+    }
+
+    fn copy_mut(&mut self) -> &mut VirtualCopy {
+        &mut self.copies[self.active_copy]
+    }
+
+    fn edit_mask(&mut self, index: usize, apply: impl FnOnce(&mut Mask)) -> bool {
+        if index >= self.copy().state.masks.len() {
+            return false;
+        }
+        self.edit(EditCommand::EditMask(index), |state| {
+            if let Some(mask) = state.mask_mut(index) {
+                apply(mask);
+            }
+        });
+        true
+    }
+
+    fn edit_layer(&mut self, index: usize, apply: impl FnOnce(&mut Layer)) -> bool {
+        if index >= self.copy().state.layers.len() {
+            return false;
+        }
+        self.edit(EditCommand::EditLayer(index), |state| {
+            if let Some(layer) = state.layer_mut(index) {
+                apply(layer);
+            }
+        });
+        true
+    }
+
+    /// 编辑状态已变化：请求渲染并通知监听者
+    fn changed(&mut self) {
+        self.render_requested = true;
+        if let Some(listener) = self.change_listener.as_mut() {
+            listener();
+        }
     }
 
     /// 请求渲染
@@ -127,8 +835,77 @@ impl Renderer {
             return;
         }
 
-        // TODO: 实现实际渲染逻辑
-
+        self.update_brush_masks(self.active_copy);
+        self.draw(&self.copy().state);
         self.render_requested = false;
     }
+
+    /// 渲染指定的虚拟副本，不切换当前编辑的副本
+    pub fn render_copy(&mut self, index: usize) -> bool {
+        if index >= self.copies.len() {
+            return false;
+        }
+        self.update_brush_masks(index);
+        self.draw(&self.copies[index].state);
+        true
+    }
+
+    /// 画笔笔触或预览分辨率变化时，按预览分辨率重新栅格化画笔蒙版
+    fn update_brush_masks(&mut self, copy_index: usize) {
+        let Some(texture) = self.texture_manager.source_texture() else {
+            return;
+        };
+        let size = (texture.width(), texture.height());
+        let masks = &self.copies[copy_index].state.masks;
+        let brushes = || masks.iter().filter(|mask| mask.is_brush());
+
+        let unchanged = self.rasterized_size == size
+            && self
+                .rasterized_brushes
+                .iter()
+                .eq(brushes().map(|mask| &mask.shape));
+        if unchanged {
+            return;
+        }
+
+        let rasters: Vec<MaskRaster> = brushes()
+            .filter_map(|mask| match &mask.shape {
+                MaskShape::Brush { strokes } => {
+                    Some(MaskRaster::from_strokes(strokes, size.0, size.1))
+                }
+                _ => None,
+            })
+            .collect();
+        self.rasterized_brushes = brushes().map(|mask| mask.shape.clone()).collect();
+        self.rasterized_size = size;
+        self.texture_manager
+            .update_brush_texture(&self.device, &self.queue, &rasters);
+    }
+
+    /// 用编辑状态从原图渲染
+    fn draw(&self, state: &EditState) {
+        // 上传最新的参数
+        self.texture_manager
+            .update_uniform_buffer(&self.queue, &state.adjustments);
+        self.texture_manager
+            .update_grading_buffer(&self.queue, &state.grading);
+        self.texture_manager.update_output_buffer(
+            &self.queue,
+            self.source_color_space,
+            self.display_color_space,
+        );
+        let plan = self.plan_for(state);
+        let to_source_uv = plan.map_or(Homography::IDENTITY, |plan| plan.to_source_uv());
+        self.texture_manager
+            .update_geometry_buffer(&self.queue, &to_source_uv);
+        self.texture_manager
+            .update_comparison_buffer(&self.queue, &self.comparison);
+        let aspect = self
+            .image_size()
+            .map_or(1.0, |(width, height)| width as f32 / height as f32);
+        self.texture_manager
+            .update_mask_buffer(&self.queue, &state.masks, aspect);
+
+        // TODO: 实现实际渲染逻辑
+    }
 }
@@ -1,6 +1,12 @@
 //! 纹理管理模块
 
-use editor_studio_params::AdjustmentParams;
+use bytemuck::Zeroable;
+use editor_studio_math::{Homography, MaskRaster};
+use editor_studio_params::{
+    AdjustmentParams, ColorGrading, ColorSpace, ComparisonMode, ComparisonState, Mask, MaskShape,
+    RangeSelection, SplitOrientation, MAX_BRUSH_MASKS, MAX_MASKS,
+};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 /// 纹理管理器
@@ -8,6 +14,140 @@ pub struct TextureManager {
     source_texture: Option<wgpu::Texture>,
     bind_group: Option<wgpu::BindGroup>,
     uniform_buffer: wgpu::Buffer,
+    grading_buffer: wgpu::Buffer,
+    output_buffer: wgpu::Buffer,
+    geometry_buffer: wgpu::Buffer,
+    comparison_buffer: wgpu::Buffer,
+    mask_buffer: wgpu::Buffer,
+    brush_texture: wgpu::Texture,
+    /// 按图像编号保存的图层纹理
+    layer_textures: HashMap<u32, wgpu::Texture>,
+}
+
+/// WGSL `mat3x3<f32>` uniform (每列按 16 字节对齐)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Mat3Uniform {
+    columns: [[f32; 4]; 3],
+}
+
+impl Mat3Uniform {
+    /// 从行优先的矩阵创建
+    fn from_rows(m: [[f32; 3]; 3]) -> Self {
+        Self {
+            columns: [0, 1, 2].map(|j| [m[0][j], m[1][j], m[2][j], 0.0]),
+        }
+    }
+}
+
+/// 前后对比 uniform (与 WGSL 中的 `Comparison` 一致)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ComparisonUniform {
+    mode: u32,
+    orientation: u32,
+    position: f32,
+    divider: u32,
+}
+
+impl ComparisonUniform {
+    fn new(state: &ComparisonState) -> Self {
+        let mode = match state.mode {
+            ComparisonMode::Split => 1,
+            ComparisonMode::SideBySide => 2,
+            ComparisonMode::PressAndHold if state.holding => 3,
+            ComparisonMode::Off | ComparisonMode::PressAndHold => 0,
+        };
+        Self {
+            mode,
+            orientation: match state.orientation {
+                SplitOrientation::Vertical => 0,
+                SplitOrientation::Horizontal => 1,
+            },
+            position: state.position,
+            divider: state.show_divider as u32,
+        }
+    }
+}
+
+/// 单个蒙版 uniform (与 WGSL 中的 `Mask` 一致，80 字节)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MaskUniform {
+    kind: u32,
+    invert: u32,
+    feather: f32,
+    angle: f32,
+    center: [f32; 2],
+    radius: [f32; 2],
+    channel: u32,
+    range_kind: u32,
+    range_smoothness: f32,
+    _padding: u32,
+    range: [f32; 4],
+    adjustments: AdjustmentParams,
+}
+
+impl MaskUniform {
+    /// # 参数
+    /// * `channel` - 画笔蒙版在蒙版纹理中的通道
+    fn new(mask: &Mask, channel: u32) -> Self {
+        let (kind, center, radius, angle, feather) = match mask.shape {
+            MaskShape::Linear {
+                x,
+                y,
+                angle,
+                feather,
+            } => (0, [x, y], [0.0; 2], angle, feather),
+            MaskShape::Radial {
+                x,
+                y,
+                radius_x,
+                radius_y,
+                rotation,
+                feather,
+            } => (1, [x, y], [radius_x, radius_y], rotation, feather),
+            MaskShape::Brush { .. } => (2, [0.0; 2], [0.0; 2], 0.0, 0.0),
+            MaskShape::Global => (3, [0.0; 2], [0.0; 2], 0.0, 0.0),
+        };
+        let (range_kind, range, range_smoothness) = match mask.range {
+            None => (0, [0.0; 4], 0.0),
+            Some(RangeSelection::Luminance {
+                low,
+                high,
+                smoothness,
+            }) => (1, [low, high, 0.0, 0.0], smoothness),
+            Some(RangeSelection::Color {
+                color,
+                tolerance,
+                smoothness,
+            }) => (2, [color[0], color[1], color[2], tolerance], smoothness),
+        };
+        Self {
+            kind,
+            invert: mask.invert as u32,
+            feather,
+            angle,
+            center,
+            radius,
+            channel,
+            range_kind,
+            range_smoothness,
+            _padding: 0,
+            range,
+            adjustments: mask.adjustments,
+        }
+    }
+}
+
+/// 蒙版数组 uniform (与 WGSL 中的 `Masks` 一致)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MasksUniform {
+    count: u32,
+    aspect: f32,
+    _padding: [f32; 2],
+    items: [MaskUniform; MAX_MASKS],
 }
 
 impl TextureManager {
@@ -21,14 +161,64 @@ impl TextureManager {
             mapped_at_creation: false,
         });
 
+        // 创建 uniform buffer 用于色彩分级参数
+        let grading_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Color Grading Buffer"),
+            size: std::mem::size_of::<ColorGrading>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // 创建 uniform buffer 用于输出色彩空间转换
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Output Transform Buffer"),
+            size: std::mem::size_of::<Mat3Uniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // 创建 uniform buffer 用于几何变换
+        let geometry_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Geometry Transform Buffer"),
+            size: std::mem::size_of::<Mat3Uniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // 创建 uniform buffer 用于前后对比
+        let comparison_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Comparison Buffer"),
+            size: std::mem::size_of::<ComparisonUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // 创建 uniform buffer 用于局部调整蒙版
+        let mask_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mask Buffer"),
+            size: std::mem::size_of::<MasksUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         Self {
+            brush_texture: create_brush_texture(device, 1, 1),
+            layer_textures: HashMap::new(),
             source_texture: None,
             bind_group: None,
             uniform_buffer,
+            grading_buffer,
+            output_buffer,
+            geometry_buffer,
+            comparison_buffer,
+            mask_buffer,
         }
     }
 
     /// 加载图像数据到纹理
+    ///
+    /// Display P3 与 sRGB 使用相同的传递函数，两者都以 `Rgba8UnormSrgb` 上传，
+    /// 着色器采样得到的是对应色彩空间的线性值。
     pub fn load_image(
         &mut self,
         device: &wgpu::Device,
@@ -92,4 +282,202 @@ impl TextureManager {
     pub fn update_uniform_buffer(&self, queue: &wgpu::Queue, params: &AdjustmentParams) {
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(params));
     }
+
+    /// 获取色彩分级 uniform buffer
+    pub fn grading_buffer(&self) -> &wgpu::Buffer {
+        &self.grading_buffer
+    }
+
+    /// 更新色彩分级 uniform buffer 数据
+    pub fn update_grading_buffer(&self, queue: &wgpu::Queue, grading: &ColorGrading) {
+        queue.write_buffer(&self.grading_buffer, 0, bytemuck::bytes_of(grading));
+    }
+
+    /// 获取输出色彩空间转换 uniform buffer
+    pub fn output_buffer(&self) -> &wgpu::Buffer {
+        &self.output_buffer
+    }
+
+    /// 更新输出色彩空间转换 uniform buffer 数据
+    pub fn update_output_buffer(
+        &self,
+        queue: &wgpu::Queue,
+        source: ColorSpace,
+        display: ColorSpace,
+    ) {
+        let transform = Mat3Uniform::from_rows(source.conversion_matrix(display));
+        queue.write_buffer(&self.output_buffer, 0, bytemuck::bytes_of(&transform));
+    }
+
+    /// 获取几何变换 uniform buffer
+    pub fn geometry_buffer(&self) -> &wgpu::Buffer {
+        &self.geometry_buffer
+    }
+
+    /// 更新几何变换 uniform buffer 数据
+    ///
+    /// # 参数
+    /// * `to_source_uv` - 输出 UV 到原图 UV 的变换
+    pub fn update_geometry_buffer(&self, queue: &wgpu::Queue, to_source_uv: &Homography) {
+        let transform = Mat3Uniform::from_rows(to_source_uv.to_mat3());
+        queue.write_buffer(&self.geometry_buffer, 0, bytemuck::bytes_of(&transform));
+    }
+
+    /// 获取前后对比 uniform buffer
+    pub fn comparison_buffer(&self) -> &wgpu::Buffer {
+        &self.comparison_buffer
+    }
+
+    /// 更新前后对比 uniform buffer 数据
+    pub fn update_comparison_buffer(&self, queue: &wgpu::Queue, comparison: &ComparisonState) {
+        let uniform = ComparisonUniform::new(comparison);
+        queue.write_buffer(&self.comparison_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// 获取蒙版 uniform buffer
+    pub fn mask_buffer(&self) -> &wgpu::Buffer {
+        &self.mask_buffer
+    }
+
+    /// 更新蒙版 uniform buffer 数据，超出上限的蒙版被忽略
+    ///
+    /// # 参数
+    /// * `aspect` - 原图宽高比 (宽 / 高)
+    pub fn update_mask_buffer(&self, queue: &wgpu::Queue, masks: &[Mask], aspect: f32) {
+        let mut uniform = MasksUniform::zeroed();
+        // 画笔蒙版按出现顺序占用蒙版纹理的通道
+        let mut channel = 0;
+        for (item, mask) in uniform.items.iter_mut().zip(masks) {
+            *item = MaskUniform::new(mask, channel);
+            if mask.is_brush() {
+                channel += 1;
+            }
+        }
+        uniform.count = masks.len().min(MAX_MASKS) as u32;
+        uniform.aspect = aspect;
+        queue.write_buffer(&self.mask_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// 获取画笔蒙版纹理
+    pub fn brush_texture(&self) -> &wgpu::Texture {
+        &self.brush_texture
+    }
+
+    /// 上传栅格化的画笔蒙版，每个蒙版占用 RGBA 纹理的一个通道
+    ///
+    /// 所有栅格的尺寸必须相同，超出 `MAX_BRUSH_MASKS` 的栅格被忽略。
+    pub fn update_brush_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        rasters: &[MaskRaster],
+    ) {
+        let (width, height) = rasters
+            .first()
+            .map_or((1, 1), |raster| (raster.width, raster.height));
+
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        for (channel, raster) in rasters.iter().take(MAX_BRUSH_MASKS).enumerate() {
+            for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+                pixel[channel] = raster.get(i as u32 % width, i as u32 / width);
+            }
+        }
+
+        if self.brush_texture.width() != width || self.brush_texture.height() != height {
+            self.brush_texture = create_brush_texture(device, width, height);
+        }
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.brush_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// 上传图层图像 (RGBA8，与原图处于同一色彩空间)，替换同编号的旧纹理
+    pub fn load_layer_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: u32,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Layer Image Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.layer_textures.insert(image, texture);
+    }
+
+    /// 删除图层图像的纹理
+    pub fn remove_layer_image(&mut self, image: u32) -> bool {
+        self.layer_textures.remove(&image).is_some()
+    }
+
+    /// 获取图层图像的纹理
+    pub fn layer_texture(&self, image: u32) -> Option<&wgpu::Texture> {
+        self.layer_textures.get(&image)
+    }
+}
+
+/// 创建画笔蒙版纹理 (线性 RGBA8，每个通道一个蒙版)
+fn create_brush_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Brush Mask Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
 }
@@ -2,6 +2,20 @@
 //!
 //! 这是 WASM 模块的主入口点，通过 wasm-bindgen 导出 API 给 JavaScript。
 
+use editor_studio_image::{
+    AutoStraighten, ColorManager, EditPipeline, ExportConfig, Image, ImageAdjust, ImageEncoder,
+    ImageTransform, LayerImages, Proxy, Saliency, SubjectDetector, Watermark, WatermarkAnchor,
+    DEFAULT_PROXY_DIMENSION,
+};
+use editor_studio_math::{
+    CropDragOptions, CropHandle, CropScorer, CropSuggester, ImageSize, Subject, WeightedComposition,
+};
+use editor_studio_params::{
+    AdjustmentClipboard, AdjustmentParams, BlendMode, BrushStroke, ColorSpace, ComparisonMode,
+    CropRect, CropTransform, EditOperation, EditRecipe, EditState, Layer, LayerTransform, Mask,
+    MaskShape, PasteGroups, Preset, PresetLibrary, RangeSelection, SplitOrientation, ToneRange,
+    MAX_BRUSH_MASKS, MAX_LAYERS, MAX_MASKS,
+};
 use wasm_bindgen::prelude::*;
 
 // 在控制台显示 panic 信息
@@ -14,6 +28,20 @@ pub fn init() {
 #[wasm_bindgen]
 pub struct WasmImageEditor {
     renderer: Option<editor_studio_renderer::Renderer>,
+    /// 解码后的原图，编辑只记录在编辑状态中，从不修改原图
+    source: Option<Image>,
+    /// 交互预览代理的最长边
+    proxy_dimension: u32,
+    presets: PresetLibrary,
+    /// 图层引用的图像 (与原图处于同一色彩空间)，所有虚拟副本共享
+    layer_images: LayerImages,
+    next_layer_image: u32,
+    /// 导出时叠加的水印，不进入预览和编辑状态
+    watermark: Option<Watermark>,
+    /// 拖动裁剪框手柄开始时的裁剪区域
+    crop_drag_start: Option<CropRect>,
+    /// 主体检测器 (模型由应用提供)
+    detector: Option<Box<dyn SubjectDetector>>,
 }
 
 #[wasm_bindgen]
@@ -24,6 +52,14 @@ impl WasmImageEditor {
         let renderer = editor_studio_renderer::Renderer::new().await?;
         Ok(WasmImageEditor {
             renderer: Some(renderer),
+            source: None,
+            proxy_dimension: DEFAULT_PROXY_DIMENSION,
+            presets: PresetLibrary::with_builtins(),
+            layer_images: LayerImages::new(),
+            next_layer_image: 0,
+            watermark: None,
+            crop_drag_start: None,
+            detector: None,
         })
     }
 
@@ -32,30 +68,140 @@ impl WasmImageEditor {
     /// # 参数
     /// * `data` - 图像数据的字节数组 (JPEG/PNG/WEBP)
     pub fn load_image(&mut self, data: &[u8]) -> Result<(), JsValue> {
-        use editor_studio_image::Image;
-
         // 解码图像
         let image = Image::from_bytes(data)
             .map_err(|e| JsValue::from_str(&e))?;
 
-        // 上传到 GPU 纹理
+        self.source = Some(image);
+        self.upload_proxy()
+    }
+
+    /// 设置交互预览代理的最长边 (像素)，导出始终使用原图分辨率
+    #[wasm_bindgen]
+    pub fn set_proxy_size(&mut self, max_dimension: u32) -> Result<(), JsValue> {
+        self.proxy_dimension =
+            max_dimension.clamp(1, editor_studio_renderer::MAX_TEXTURE_DIMENSION);
+        self.upload_proxy()
+    }
+
+    /// 获取应用几何操作后的原图分辨率输出尺寸 `[width, height]`
+    #[wasm_bindgen]
+    pub fn get_output_size(&self) -> Vec<u32> {
+        self.renderer
+            .as_ref()
+            .and_then(|renderer| renderer.output_size())
+            .map_or_else(Vec::new, |(width, height)| vec![width, height])
+    }
+
+    /// 以原图分辨率渲染输出图像中的一个区域 (用于 1:1 查看)
+    ///
+    /// # 参数
+    /// * `x`, `y`, `width`, `height` - 区域在输出图像中的归一化坐标 (0-1)
+    ///
+    /// 返回 `{ width, height, data }`，`data` 为显示色彩空间的 RGBA8 像素。
+    #[wasm_bindgen]
+    pub fn render_region(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) -> Result<JsValue, JsValue> {
+        let source = self
+            .source
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("No image loaded"))?;
+        let renderer = self
+            .renderer
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("Renderer not initialized"))?;
+
+        let region = CropRect::new(x, y, width, height);
+        let image = EditPipeline::render_region_layered(
+            source,
+            renderer.state(),
+            &self.layer_images,
+            &region,
+        );
+        let image = ColorManager::convert(&image, renderer.display_color_space());
+
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"width".into(), &image.width.into())?;
+        js_sys::Reflect::set(&result, &"height".into(), &image.height.into())?;
+        js_sys::Reflect::set(
+            &result,
+            &"data".into(),
+            &js_sys::Uint8Array::from(image.data.as_slice()),
+        )?;
+        Ok(result.into())
+    }
+
+    /// 设置显示器的色彩空间
+    ///
+    /// # 参数
+    /// * `name` - `"srgb"` 或 `"display-p3"`
+    #[wasm_bindgen]
+    pub fn set_display_color_space(&mut self, name: &str) -> Result<(), JsValue> {
+        let color_space = ColorSpace::from_name(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown color space: {}", name)))?;
         if let Some(ref mut renderer) = self.renderer {
-            let device = renderer.device();
-            let queue = renderer.queue();
-            let texture_manager = renderer.texture_manager_mut();
+            renderer.set_display_color_space(color_space);
+        }
+        Ok(())
+    }
 
-            texture_manager.load_image(
-                device,
-                queue,
-                &image.data,
-                image.width,
-                image.height,
-            )?;
+    /// 设置前后对比模式
+    ///
+    /// # 参数
+    /// * `mode` - `"off"`、`"split"`、`"side-by-side"` 或 `"press-and-hold"`
+    ///
+    /// 并排模式在每一半中显示完整图像，画布应为单张图像的两倍宽 (或高)。
+    #[wasm_bindgen]
+    pub fn set_comparison_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode = ComparisonMode::from_name(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown comparison mode: {}", mode)))?;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_comparison_mode(mode);
         }
+        Ok(())
+    }
 
+    /// 设置分割线
+    ///
+    /// # 参数
+    /// * `orientation` - `"vertical"` (原图在左) 或 `"horizontal"` (原图在上)
+    /// * `position` - 分割线位置 (0.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_comparison_split(
+        &mut self,
+        orientation: &str,
+        position: f32,
+    ) -> Result<(), JsValue> {
+        let orientation = SplitOrientation::from_name(orientation).ok_or_else(|| {
+            JsValue::from_str(&format!("Unknown split orientation: {}", orientation))
+        })?;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_comparison_split(orientation, position);
+        }
         Ok(())
     }
 
+    /// 设置是否绘制分割线
+    #[wasm_bindgen]
+    pub fn set_comparison_divider(&mut self, visible: bool) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_comparison_divider(visible);
+        }
+    }
+
+    /// 按住/松开对比按钮 (按住对比模式下按住时显示原图)
+    #[wasm_bindgen]
+    pub fn set_comparing(&mut self, holding: bool) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_comparison_holding(holding);
+        }
+    }
+
     /// 设置亮度调整 (-1.0 到 1.0)
     #[wasm_bindgen]
     pub fn set_brightness(&mut self, value: f32) {
@@ -80,6 +226,51 @@ impl WasmImageEditor {
         }
     }
 
+    /// 设置阴影色轮
+    ///
+    /// # 参数
+    /// * `hue` - 色相 (0.0 到 360.0)
+    /// * `saturation` - 饱和度 (0.0 到 1.0)
+    /// * `luminance` - 明度偏移 (-1.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_shadows_wheel(&mut self, hue: f32, saturation: f32, luminance: f32) {
+        self.set_color_wheel(ToneRange::Shadows, hue, saturation, luminance);
+    }
+
+    /// 设置中间调色轮 (参数同 `set_shadows_wheel`)
+    #[wasm_bindgen]
+    pub fn set_midtones_wheel(&mut self, hue: f32, saturation: f32, luminance: f32) {
+        self.set_color_wheel(ToneRange::Midtones, hue, saturation, luminance);
+    }
+
+    /// 设置高光色轮 (参数同 `set_shadows_wheel`)
+    #[wasm_bindgen]
+    pub fn set_highlights_wheel(&mut self, hue: f32, saturation: f32, luminance: f32) {
+        self.set_color_wheel(ToneRange::Highlights, hue, saturation, luminance);
+    }
+
+    /// 设置全局色轮 (参数同 `set_shadows_wheel`)
+    #[wasm_bindgen]
+    pub fn set_global_wheel(&mut self, hue: f32, saturation: f32, luminance: f32) {
+        self.set_color_wheel(ToneRange::Global, hue, saturation, luminance);
+    }
+
+    /// 设置色彩分级混合程度 (0.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_grading_blending(&mut self, value: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_grading_blending(value);
+        }
+    }
+
+    /// 设置色彩分级平衡 (-1.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_grading_balance(&mut self, value: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_grading_balance(value);
+        }
+    }
+
     /// 重置所有调整
     #[wasm_bindgen]
     pub fn reset_adjustments(&mut self) {
@@ -88,6 +279,931 @@ impl WasmImageEditor {
         }
     }
 
+    /// 设置裁剪区域 (归一化坐标 0-1)
+    #[wasm_bindgen]
+    pub fn set_crop(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_crop(Some(CropRect::new(x, y, width, height)));
+        }
+    }
+
+    /// 清除裁剪区域
+    #[wasm_bindgen]
+    pub fn clear_crop(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_crop(None);
+        }
+    }
+
+    /// 获取当前裁剪区域 `[x, y, width, height]`，没有时为空数组
+    #[wasm_bindgen]
+    pub fn get_crop(&self) -> Vec<f32> {
+        self.renderer
+            .as_ref()
+            .and_then(|renderer| renderer.crop())
+            .map_or_else(Vec::new, |rect| {
+                vec![rect.x, rect.y, rect.width, rect.height]
+            })
+    }
+
+    /// 开始拖动裁剪框手柄，记录当前裁剪区域 (没有时为整张图像)
+    #[wasm_bindgen]
+    pub fn begin_crop_drag(&mut self) {
+        self.crop_drag_start = self.renderer.as_ref().map(|renderer| {
+            renderer
+                .crop()
+                .unwrap_or_else(|| CropRect::new(0.0, 0.0, 1.0, 1.0))
+        });
+    }
+
+    /// 拖动裁剪框手柄，返回新的裁剪区域 `[x, y, width, height, ratio]` (ratio 为 0 表示自由)
+    ///
+    /// # 参数
+    /// * `handle` - 手柄名称 ("top-left", "top", "top-right", "right", "bottom-right",
+    ///   "bottom", "bottom-left", "left", "move")
+    /// * `dx`, `dy` - 从 `begin_crop_drag` 开始累计的位移 (归一化坐标)
+    /// * `ratio` - 锁定的像素宽高比，0 表示自由
+    /// * `min_size` - 裁剪框的最小边长 (像素)
+    /// * `from_center` - 是否以中心为固定点缩放
+    #[wasm_bindgen]
+    pub fn drag_crop_handle(
+        &mut self,
+        handle: &str,
+        dx: f32,
+        dy: f32,
+        ratio: f32,
+        min_size: f32,
+        from_center: bool,
+    ) -> Result<Vec<f32>, JsValue> {
+        let handle = CropHandle::from_name(handle)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown crop handle: {}", handle)))?;
+        let start = self
+            .crop_drag_start
+            .ok_or_else(|| JsValue::from_str("Crop drag not started"))?;
+        let renderer = self
+            .renderer
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Renderer not initialized"))?;
+        let options = CropDragOptions {
+            ratio: (ratio > 0.0).then_some(ratio),
+            min_size,
+            from_center,
+            ..CropDragOptions::default()
+        };
+        let rect = renderer
+            .drag_crop_handle(&start, handle, dx, dy, &options)
+            .ok_or_else(|| JsValue::from_str("No image loaded"))?;
+        Ok(vec![
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            rect.ratio.unwrap_or(0.0),
+        ])
+    }
+
+    /// 生成裁剪建议 (多裁剪预览条)
+    ///
+    /// 返回扁平数组，每个建议 12 个值 `[ratio, x, y, width, height, score, composition,
+    /// containment, framing, area, cut_penalty, cut_subjects]`，后六个为评分的各项，
+    /// 用于解释建议 (没有适用的构图规则时 `composition` 为 -1)。
+    /// 按 `ratios` 的顺序分组，组内按评分从高到低排列。
+    ///
+    /// # 参数
+    /// * `subjects` - 主体 `[x, y, width, height, importance, ...]` (归一化坐标 0-1，
+    ///   重要程度 0-1，次要主体可以被排除但不会被切开)
+    /// * `faces` - 人脸 `[x, y, width, height, importance, ...]`，用于头顶留白，切开人脸扣分最多
+    /// * `ratios` - 目标像素宽高比 (宽 / 高)
+    /// * `count` - 每个宽高比的建议数
+    /// * `style` - 构图风格 ("balanced", "portrait", "symmetric", "dynamic")
+    #[wasm_bindgen]
+    pub fn suggest_crops(
+        &self,
+        subjects: Vec<f32>,
+        faces: Vec<f32>,
+        ratios: Vec<f32>,
+        count: usize,
+        style: &str,
+    ) -> Result<Vec<f32>, JsValue> {
+        let rules = WeightedComposition::from_name(style)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown composition style: {}", style)))?;
+        let rect = |s: &[f32]| CropRect::new(s[0], s[1], s[2], s[3]);
+        let subjects: Vec<Subject> = subjects
+            .chunks_exact(5)
+            .map(|s| Subject::new(rect(s)).with_importance(s[4]))
+            .chain(
+                faces
+                    .chunks_exact(5)
+                    .map(|s| Subject::face(rect(s)).with_importance(s[4])),
+            )
+            .collect();
+        let suggester = CropSuggester {
+            scorer: CropScorer::new(rules),
+            ..CropSuggester::new(count)
+        };
+        let suggestions = self
+            .renderer
+            .as_ref()
+            .and_then(|renderer| renderer.suggest_crops(&suggester, &subjects, &ratios))
+            .unwrap_or_default();
+        Ok(suggestions
+            .iter()
+            .flat_map(|group| {
+                group.suggestions.iter().flat_map(move |suggestion| {
+                    let crop = suggestion.crop;
+                    let breakdown = suggestion.breakdown;
+                    [
+                        group.ratio,
+                        crop.x,
+                        crop.y,
+                        crop.width,
+                        crop.height,
+                        suggestion.score,
+                        breakdown.composition.unwrap_or(-1.0),
+                        breakdown.containment,
+                        breakdown.framing,
+                        breakdown.area,
+                        breakdown.cut_penalty,
+                        breakdown.cut_subjects as f32,
+                    ]
+                })
+            })
+            .collect())
+    }
+
+    /// 用显著性估计画面主体 (不依赖模型)，坐标相对于裁剪操作的输入图像
+    ///
+    /// 返回扁平数组，每个主体 5 个值 `[x, y, width, height, importance]`，按显著程度排列，
+    /// 可以直接作为 `suggest_crops` 的 `subjects` 参数；画面没有明显主体时返回空数组。
+    #[wasm_bindgen]
+    pub fn estimate_subjects(&self) -> Vec<f32> {
+        let Some(map) = self
+            .operation_input_proxy(|op| matches!(op, EditOperation::Crop(_)))
+            .and_then(|input| Saliency::analyze(&input))
+        else {
+            return Vec::new();
+        };
+        map.subjects()
+            .iter()
+            .flat_map(|subject| {
+                let rect = subject.rect;
+                [rect.x, rect.y, rect.width, rect.height, subject.importance]
+            })
+            .collect()
+    }
+
+    /// 加载 ONNX 主体检测模型 (本地推理，不访问网络)
+    ///
+    /// # 参数
+    /// * `model` - ONNX 模型字节
+    /// * `preset` - 模型类型 ("yolo": 640x640 的 YOLO 模型，"ultraface": UltraFace 人脸检测)
+    /// * `labels` - 类别名称，顺序与模型输出一致 (人脸类别命名为 "face")；
+    ///   为空时使用预设的类别 ("yolo" 为 COCO 的 80 个类别)
+    #[cfg(feature = "onnx")]
+    #[wasm_bindgen]
+    pub fn load_detector(
+        &mut self,
+        model: &[u8],
+        preset: &str,
+        labels: Vec<String>,
+    ) -> Result<(), JsValue> {
+        use editor_studio_image::{OnnxDetector, OnnxDetectorConfig, COCO_LABELS};
+
+        let mut config = match preset.to_ascii_lowercase().as_str() {
+            "yolo" => OnnxDetectorConfig::yolo(COCO_LABELS.map(String::from).to_vec()),
+            "ultraface" => OnnxDetectorConfig::ultraface(),
+            _ => {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown detector preset: {}",
+                    preset
+                )))
+            }
+        };
+        if !labels.is_empty() {
+            config.labels = labels;
+        }
+        let detector =
+            OnnxDetector::from_bytes(model, config).map_err(|e| JsValue::from_str(&e))?;
+        self.detector = Some(Box::new(detector));
+        Ok(())
+    }
+
+    /// 卸载主体检测模型
+    #[wasm_bindgen]
+    pub fn clear_detector(&mut self) {
+        self.detector = None;
+    }
+
+    /// 是否已加载主体检测模型
+    #[wasm_bindgen]
+    pub fn has_detector(&self) -> bool {
+        self.detector.is_some()
+    }
+
+    /// 用已加载的模型检测主体，坐标相对于裁剪操作的输入图像
+    ///
+    /// 返回扁平数组，每个结果 6 个值 `[x, y, width, height, score, is_face]`，按置信度排列；
+    /// 前五个值可以直接作为 `suggest_crops` 的主体 (`is_face` 为 1 时放入 `faces`)。
+    /// 没有加载模型或图像时返回空数组。
+    #[wasm_bindgen]
+    pub fn detect_subjects(&self) -> Result<Vec<f32>, JsValue> {
+        let input = self.operation_input_proxy(|op| matches!(op, EditOperation::Crop(_)));
+        let (Some(detector), Some(input)) = (self.detector.as_ref(), input) else {
+            return Ok(Vec::new());
+        };
+        let detections = detector.detect(&input).map_err(|e| JsValue::from_str(&e))?;
+        Ok(detections
+            .iter()
+            .flat_map(|detection| {
+                let rect = detection.rect;
+                [
+                    rect.x,
+                    rect.y,
+                    rect.width,
+                    rect.height,
+                    detection.score,
+                    if detection.is_face() { 1.0 } else { 0.0 },
+                ]
+            })
+            .collect())
+    }
+
+    /// 互换裁剪区域宽高比的横竖方向 (如 3:2 变为 2:3)
+    #[wasm_bindgen]
+    pub fn swap_crop_orientation(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.swap_crop_orientation();
+        }
+    }
+
+    /// 设置带旋转角度的裁剪 (拉直)，旋转和裁剪只重采样一次
+    ///
+    /// # 参数
+    /// * `x`, `y`, `width`, `height` - 旋转前的裁剪区域 (归一化坐标 0-1)，绕其中心旋转
+    /// * `angle` - 旋转角度 (度，顺时针)
+    #[wasm_bindgen]
+    pub fn set_crop_transform(&mut self, x: f32, y: f32, width: f32, height: f32, angle: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            let rect = CropRect::new(x, y, width, height);
+            renderer.set_crop_transform(Some(CropTransform::new(rect, angle)));
+        }
+    }
+
+    /// 清除拉直裁剪
+    #[wasm_bindgen]
+    pub fn clear_crop_transform(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_crop_transform(None);
+        }
+    }
+
+    /// 按角度拉直，自动使用该角度下能放进图像的最大裁剪区域
+    #[wasm_bindgen]
+    pub fn set_straighten(&mut self, angle: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_straighten(angle);
+        }
+    }
+
+    /// 获取当前拉直裁剪 `[x, y, width, height, angle]`，没有时为空数组
+    #[wasm_bindgen]
+    pub fn get_crop_transform(&self) -> Vec<f32> {
+        self.renderer
+            .as_ref()
+            .and_then(|renderer| renderer.crop_transform())
+            .map_or_else(Vec::new, |crop| {
+                let rect = crop.rect;
+                vec![rect.x, rect.y, rect.width, rect.height, crop.angle]
+            })
+    }
+
+    /// 当前拉直裁剪绕中心允许的最大缩放倍数 (没有拉直裁剪时为 0)
+    #[wasm_bindgen]
+    pub fn get_max_crop_scale(&self) -> f32 {
+        self.renderer
+            .as_ref()
+            .and_then(|renderer| renderer.max_crop_scale())
+            .unwrap_or(0.0)
+    }
+
+    /// 自动拉直：检测画面中接近水平和竖直的直线，按建议角度拉直
+    /// (使用该角度下能放进图像的最大裁剪区域)
+    ///
+    /// 分析的是拉直之前的几何操作的结果。返回 `[angle, confidence]`，角度为度 (顺时针)，
+    /// 也可以直接用于 `set_rotation`；没有找到明显的直线时返回空数组，不修改编辑状态。
+    #[wasm_bindgen]
+    pub fn auto_straighten(&mut self) -> Vec<f32> {
+        let Some(suggestion) = self
+            .operation_input_proxy(|op| matches!(op, EditOperation::StraightenCrop(_)))
+            .and_then(|input| AutoStraighten::analyze(&input))
+        else {
+            return Vec::new();
+        };
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_straighten(suggestion.angle);
+        }
+        vec![suggestion.angle, suggestion.confidence]
+    }
+
+    /// 设置旋转角度 (度，顺时针)
+    #[wasm_bindgen]
+    pub fn set_rotation(&mut self, degrees: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_rotation(degrees);
+        }
+    }
+
+    /// 设置水平/垂直翻转
+    #[wasm_bindgen]
+    pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_flip(horizontal, vertical);
+        }
+    }
+
+    /// 设置四点透视校正，四边形被拉伸为矩形
+    ///
+    /// # 参数
+    /// * `corners` - 左上、右上、右下、左下四个角 `[x0, y0, ..., x3, y3]` (归一化坐标 0-1)
+    #[wasm_bindgen]
+    pub fn set_perspective(&mut self, corners: Vec<f32>) -> Result<(), JsValue> {
+        if corners.len() != 8 {
+            return Err(JsValue::from_str("Perspective needs four corners"));
+        }
+        let corners = std::array::from_fn(|i| [corners[2 * i], corners[2 * i + 1]]);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_perspective(Some(corners));
+        }
+        Ok(())
+    }
+
+    /// 清除四点透视校正
+    #[wasm_bindgen]
+    pub fn clear_perspective(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_perspective(None);
+        }
+    }
+
+    /// 获取四点透视校正的角点 `[x0, y0, ..., x3, y3]`，没有时为空数组
+    #[wasm_bindgen]
+    pub fn get_perspective(&self) -> Vec<f32> {
+        self.renderer
+            .as_ref()
+            .and_then(|renderer| renderer.perspective())
+            .map_or_else(Vec::new, |corners| corners.concat())
+    }
+
+    /// 设置梯形校正 (-1.0 到 1.0)
+    ///
+    /// # 参数
+    /// * `vertical` - 为正时拉宽图像顶部 (校正仰拍建筑的汇聚竖线)
+    /// * `horizontal` - 为正时拉高图像左侧
+    #[wasm_bindgen]
+    pub fn set_keystone(&mut self, vertical: f32, horizontal: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_keystone(vertical, horizontal);
+        }
+    }
+
+    /// 获取梯形校正 `[vertical, horizontal]`
+    #[wasm_bindgen]
+    pub fn get_keystone(&self) -> Vec<f32> {
+        let (vertical, horizontal) = self
+            .renderer
+            .as_ref()
+            .map_or((0.0, 0.0), |renderer| renderer.keystone());
+        vec![vertical, horizontal]
+    }
+
+    /// 添加线性渐变蒙版，返回蒙版序号
+    ///
+    /// # 参数
+    /// * `x`, `y` - 中心点 (原图归一化坐标 0-1)
+    /// * `angle` - 生效方向 (度，0 指向右侧，顺时针)
+    /// * `feather` - 过渡带宽度 (原图高度的比例)
+    #[wasm_bindgen]
+    pub fn add_linear_mask(
+        &mut self,
+        x: f32,
+        y: f32,
+        angle: f32,
+        feather: f32,
+    ) -> Result<usize, JsValue> {
+        self.add_mask(Mask::linear(x, y, angle, feather))
+    }
+
+    /// 添加径向渐变蒙版，返回蒙版序号
+    ///
+    /// # 参数
+    /// * `x`, `y` - 中心点 (原图归一化坐标 0-1)
+    /// * `radius_x`, `radius_y` - 半径 (原图宽度/高度的比例)
+    /// * `rotation` - 旋转角度 (度，顺时针)
+    /// * `feather` - 羽化 (0.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn add_radial_mask(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        feather: f32,
+    ) -> Result<usize, JsValue> {
+        self.add_mask(Mask::radial(x, y, radius_x, radius_y, rotation, feather))
+    }
+
+    /// 添加空白的画笔蒙版，返回蒙版序号
+    #[wasm_bindgen]
+    pub fn add_brush_mask(&mut self) -> Result<usize, JsValue> {
+        self.add_mask(Mask::brush())
+    }
+
+    /// 给画笔蒙版添加一个笔触 (在松开鼠标时调用，每个笔触单独撤销)
+    ///
+    /// # 参数
+    /// * `index` - 画笔蒙版序号
+    /// * `points` - 笔触经过的点 `[x0, y0, x1, y1, ...]` (原图归一化坐标 0-1)
+    /// * `size` - 笔刷直径 (原图高度的比例)
+    /// * `feather` - 羽化 (0.0 到 1.0)
+    /// * `flow` - 流量 (0.0 到 1.0)
+    /// * `erase` - 是否擦除
+    #[wasm_bindgen]
+    pub fn add_brush_stroke(
+        &mut self,
+        index: usize,
+        points: Vec<f32>,
+        size: f32,
+        feather: f32,
+        flow: f32,
+        erase: bool,
+    ) -> bool {
+        let mut stroke = BrushStroke::new(size, feather, flow, erase);
+        for point in points.chunks_exact(2) {
+            stroke.add_point(point[0], point[1]);
+        }
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.add_brush_stroke(index, stroke))
+    }
+
+    /// 修改线性渐变蒙版 (参数同 `add_linear_mask`)
+    #[wasm_bindgen]
+    pub fn set_linear_mask(
+        &mut self,
+        index: usize,
+        x: f32,
+        y: f32,
+        angle: f32,
+        feather: f32,
+    ) -> bool {
+        let shape = MaskShape::Linear {
+            x,
+            y,
+            angle,
+            feather,
+        };
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_shape(index, shape))
+    }
+
+    /// 修改径向渐变蒙版 (参数同 `add_radial_mask`)
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_radial_mask(
+        &mut self,
+        index: usize,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        feather: f32,
+    ) -> bool {
+        let shape = MaskShape::Radial {
+            x,
+            y,
+            radius_x,
+            radius_y,
+            rotation,
+            feather,
+        };
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_shape(index, shape))
+    }
+
+    /// 设置蒙版是否反转 (作用于形状之外)
+    #[wasm_bindgen]
+    pub fn set_mask_invert(&mut self, index: usize, invert: bool) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_invert(index, invert))
+    }
+
+    /// 添加作用于整张图像的范围蒙版，返回蒙版序号 (再用 `set_luminance_range` 或 `set_color_range` 设置范围)
+    #[wasm_bindgen]
+    pub fn add_range_mask(&mut self) -> Result<usize, JsValue> {
+        self.add_mask(Mask::new(MaskShape::Global))
+    }
+
+    /// 按亮度范围限制蒙版 (与蒙版形状相交)
+    ///
+    /// # 参数
+    /// * `low`, `high` - 亮度范围 (0.0 到 1.0)
+    /// * `smoothness` - 范围两端的过渡宽度 (0.0 到 1.0)
+    ///
+    /// 参数不是有限值时返回 false，不修改蒙版。
+    #[wasm_bindgen]
+    pub fn set_luminance_range(
+        &mut self,
+        index: usize,
+        low: f32,
+        high: f32,
+        smoothness: f32,
+    ) -> bool {
+        let Some(range) = RangeSelection::luminance(low, high, smoothness) else {
+            return false;
+        };
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_range(index, Some(range)))
+    }
+
+    /// 按颜色范围限制蒙版 (与蒙版形状相交)
+    ///
+    /// # 参数
+    /// * `r`, `g`, `b` - 取样颜色 (`sample_color` 返回的线性值)
+    /// * `tolerance` - 容差 (0.0 到 1.0)
+    /// * `smoothness` - 容差之外的过渡宽度 (0.0 到 1.0)
+    ///
+    /// 参数不是有限值时返回 false，不修改蒙版。
+    #[wasm_bindgen]
+    pub fn set_color_range(
+        &mut self,
+        index: usize,
+        r: f32,
+        g: f32,
+        b: f32,
+        tolerance: f32,
+        smoothness: f32,
+    ) -> bool {
+        let Some(range) = RangeSelection::color([r, g, b], tolerance, smoothness) else {
+            return false;
+        };
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_range(index, Some(range)))
+    }
+
+    /// 清除蒙版的范围选择
+    #[wasm_bindgen]
+    pub fn clear_mask_range(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_range(index, None))
+    }
+
+    /// 读取原图归一化坐标处的颜色 `[r, g, b]` (线性值，用于颜色范围取样)
+    #[wasm_bindgen]
+    pub fn sample_color(&self, x: f32, y: f32) -> Vec<f32> {
+        self.source
+            .as_ref()
+            .and_then(|source| ImageAdjust::sample_linear(source, x, y))
+            .map_or_else(Vec::new, |rgb| rgb.to_vec())
+    }
+
+    /// 设置蒙版区域内的调整参数 (取值范围同全局调整)
+    #[wasm_bindgen]
+    pub fn set_mask_adjustments(
+        &mut self,
+        index: usize,
+        brightness: f32,
+        contrast: f32,
+        saturation: f32,
+    ) -> bool {
+        let mut adjustments = AdjustmentParams::default();
+        adjustments.set_brightness(brightness);
+        adjustments.set_contrast(contrast);
+        adjustments.set_saturation(saturation);
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_mask_adjustments(index, adjustments))
+    }
+
+    /// 删除蒙版
+    #[wasm_bindgen]
+    pub fn remove_mask(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.remove_mask(index))
+    }
+
+    /// 当前蒙版数量
+    #[wasm_bindgen]
+    pub fn mask_count(&self) -> usize {
+        self.renderer
+            .as_ref()
+            .map_or(0, |renderer| renderer.masks().len())
+    }
+
+    /// 添加图像图层 (标志、纹理、第二张照片) 到最上面，返回图层序号
+    ///
+    /// 图层图像转换到原图的色彩空间保存，导出时以完整分辨率合成。
+    #[wasm_bindgen]
+    pub fn add_image_layer(&mut self, data: &[u8], name: &str) -> Result<usize, JsValue> {
+        let mut image = Image::from_bytes(data).map_err(|e| JsValue::from_str(&e))?;
+        if let Some(source) = self.source.as_ref() {
+            image = ColorManager::convert(&image, source.color_space);
+        }
+        let renderer = self
+            .renderer
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Renderer not initialized"))?;
+
+        let id = self.next_layer_image;
+        let proxy = Proxy::new(&image, self.proxy_dimension);
+        renderer.load_layer_image(id, &proxy.image.data, proxy.image.width, proxy.image.height)?;
+        let index = renderer.add_layer(Layer::new(name, id)).ok_or_else(|| {
+            JsValue::from_str(&format!("At most {} layers are supported", MAX_LAYERS))
+        })?;
+        self.layer_images.insert(id, image);
+        self.next_layer_image += 1;
+        Ok(index)
+    }
+
+    /// 设置图层位置
+    ///
+    /// # 参数
+    /// * `x`, `y` - 图层中心 (输出图像归一化坐标)
+    /// * `scale` - 图层宽度占输出图像宽度的比例
+    /// * `rotation` - 旋转角度 (度，顺时针)
+    #[wasm_bindgen]
+    pub fn set_layer_transform(
+        &mut self,
+        index: usize,
+        x: f32,
+        y: f32,
+        scale: f32,
+        rotation: f32,
+    ) -> bool {
+        let transform = LayerTransform {
+            x,
+            y,
+            scale,
+            rotation,
+        };
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_layer_transform(index, transform))
+    }
+
+    /// 设置图层不透明度 (0.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_layer_opacity(&mut self, index: usize, opacity: f32) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_layer_opacity(index, opacity))
+    }
+
+    /// 设置图层混合模式
+    ///
+    /// # 参数
+    /// * `mode` - `"normal"`、`"multiply"`、`"screen"`、`"overlay"`、`"soft-light"`、
+    ///   `"hard-light"`、`"darken"`、`"lighten"`、`"difference"` 或 `"add"`
+    #[wasm_bindgen]
+    pub fn set_layer_blend_mode(&mut self, index: usize, mode: &str) -> Result<bool, JsValue> {
+        let mode = BlendMode::from_name(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown blend mode: {}", mode)))?;
+        Ok(self
+            .renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_layer_blend_mode(index, mode)))
+    }
+
+    /// 显示或隐藏图层
+    #[wasm_bindgen]
+    pub fn set_layer_visible(&mut self, index: usize, visible: bool) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.set_layer_visible(index, visible))
+    }
+
+    /// 删除图层
+    #[wasm_bindgen]
+    pub fn remove_layer(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.remove_layer(index))
+    }
+
+    /// 调整图层顺序 (序号 0 在最下面)
+    #[wasm_bindgen]
+    pub fn move_layer(&mut self, from: usize, to: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.move_layer(from, to))
+    }
+
+    /// 图层名称列表 (从下到上)
+    #[wasm_bindgen]
+    pub fn list_layers(&self) -> Vec<String> {
+        self.renderer
+            .as_ref()
+            .map(|renderer| renderer.layers().iter().map(|l| l.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 删除几何操作栈中指定位置的操作，其余操作从原图重新计算
+    #[wasm_bindgen]
+    pub fn remove_operation(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.remove_operation(index))
+    }
+
+    /// 移动几何操作栈中的操作
+    #[wasm_bindgen]
+    pub fn move_operation(&mut self, from: usize, to: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.move_operation(from, to))
+    }
+
+    /// 获取当前编辑状态的配方 (带版本号的 JSON)
+    #[wasm_bindgen]
+    pub fn get_recipe(&self) -> Result<String, JsValue> {
+        let state = self
+            .renderer
+            .as_ref()
+            .map(|renderer| renderer.state().clone())
+            .unwrap_or_default();
+        EditRecipe::new(state)
+            .to_json()
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// 应用配方，旧版本的配方会先迁移到当前格式
+    ///
+    /// # 参数
+    /// * `json` - `get_recipe` 生成的 JSON
+    #[wasm_bindgen]
+    pub fn apply_recipe(&mut self, json: &str) -> Result<(), JsValue> {
+        let recipe = EditRecipe::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_state(recipe.state);
+        }
+        Ok(())
+    }
+
+    /// 复制当前的调整参数，返回可粘贴到其他图像的 JSON
+    #[wasm_bindgen]
+    pub fn copy_adjustments(&self) -> Result<String, JsValue> {
+        let clipboard = self
+            .renderer
+            .as_ref()
+            .and_then(|renderer| renderer.copy_adjustments())
+            .ok_or_else(|| JsValue::from_str("No image loaded"))?;
+        clipboard.to_json().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// 粘贴调整参数
+    ///
+    /// # 参数
+    /// * `json` - `copy_adjustments` 返回的 JSON
+    /// * `groups` - 要粘贴的分组：`basic_tone`、`color`、`detail` (目前不修改任何内容)、`crop`、
+    ///   `geometry`、`masks`
+    #[wasm_bindgen]
+    pub fn paste_adjustments(&mut self, json: &str, groups: Vec<String>) -> Result<(), JsValue> {
+        let clipboard = AdjustmentClipboard::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        let groups = PasteGroups::from_names(&groups).map_err(|e| JsValue::from_str(&e))?;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.paste_adjustments(&clipboard, groups);
+        }
+        Ok(())
+    }
+
+    /// 列出所有预设名称
+    #[wasm_bindgen]
+    pub fn list_presets(&self) -> Vec<String> {
+        self.presets.names()
+    }
+
+    /// 应用预设
+    ///
+    /// # 参数
+    /// * `name` - 预设名称
+    /// * `intensity` - 强度 (0 到 100)
+    #[wasm_bindgen]
+    pub fn apply_preset(&mut self, name: &str, intensity: f32) -> Result<(), JsValue> {
+        let preset = self
+            .presets
+            .get(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown preset: {}", name)))?;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.apply_preset(preset, intensity / 100.0);
+        }
+        Ok(())
+    }
+
+    /// 把当前调整保存为预设
+    #[wasm_bindgen]
+    pub fn save_preset(&mut self, name: &str) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Preset name must not be empty"));
+        }
+        if let Some(ref renderer) = self.renderer {
+            self.presets.add(Preset::from_state(name, renderer.state()));
+        }
+        Ok(())
+    }
+
+    /// 删除预设
+    #[wasm_bindgen]
+    pub fn remove_preset(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    /// 从 JSON 导入预设 (单个预设或数组)，返回导入的数量
+    #[wasm_bindgen]
+    pub fn import_presets(&mut self, json: &str) -> Result<usize, JsValue> {
+        self.presets
+            .import_json(json)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// 导出全部预设为 JSON
+    #[wasm_bindgen]
+    pub fn export_presets(&self) -> Result<String, JsValue> {
+        self.presets
+            .export_json()
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// 结束当前的连续编辑 (在滑块或裁剪框拖动结束时调用)
+    ///
+    /// 拖动过程中同一参数的多次修改会合并为一步撤销。
+    #[wasm_bindgen]
+    pub fn end_edit(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.end_edit();
+        }
+    }
+
+    /// 撤销一步，没有可撤销的步骤时返回 false
+    #[wasm_bindgen]
+    pub fn undo(&mut self) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.undo())
+    }
+
+    /// 重做一步，没有可重做的步骤时返回 false
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.redo())
+    }
+
+    /// 是否可以撤销
+    #[wasm_bindgen]
+    pub fn can_undo(&self) -> bool {
+        self.renderer
+            .as_ref()
+            .is_some_and(|renderer| renderer.can_undo())
+    }
+
+    /// 是否可以重做
+    #[wasm_bindgen]
+    pub fn can_redo(&self) -> bool {
+        self.renderer
+            .as_ref()
+            .is_some_and(|renderer| renderer.can_redo())
+    }
+
+    /// 设置历史记录的内存预算 (字节)
+    #[wasm_bindgen]
+    pub fn set_history_budget(&mut self, bytes: usize) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.history_mut().set_memory_budget(bytes);
+        }
+    }
+
+    /// 设置编辑状态变化 (编辑、撤销、重做) 时的回调，传入 `null` 取消
+    #[wasm_bindgen]
+    pub fn set_on_change(&mut self, callback: Option<js_sys::Function>) {
+        if let Some(ref mut renderer) = self.renderer {
+            let listener = callback.map(|callback| {
+                Box::new(move || {
+                    let _ = callback.call0(&JsValue::NULL);
+                }) as Box<dyn FnMut()>
+            });
+            renderer.set_change_listener(listener);
+        }
+    }
+
     /// 请求渲染一帧
     #[wasm_bindgen]
     pub fn render(&mut self) -> Result<(), JsValue> {
@@ -103,7 +1219,281 @@ impl WasmImageEditor {
     /// * `quality` - JPEG 质量 (0-100)
     #[wasm_bindgen]
     pub fn export_jpeg(&self, quality: u8) -> Result<JsValue, JsValue> {
-        // TODO: 实现导出逻辑
-        Ok(JsValue::NULL)
+        let state = self
+            .renderer
+            .as_ref()
+            .map(|renderer| renderer.state().clone())
+            .unwrap_or_default();
+        self.export_state(&state, quality)
+    }
+
+    /// 使用图像 (通常是 PNG 标志) 作为导出水印，保留已有的位置和不透明度设置
+    #[wasm_bindgen]
+    pub fn set_watermark_image(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        let logo = Image::from_bytes(data).map_err(|e| JsValue::from_str(&e))?;
+        self.set_watermark_content(Watermark::image(logo));
+        Ok(())
+    }
+
+    /// 使用文字作为导出水印，保留已有的位置和不透明度设置
+    ///
+    /// # 参数
+    /// * `text` - 水印文字 (可以有多行)
+    /// * `font_data` - TTF 或 OTF 字体文件的内容
+    /// * `r`, `g`, `b` - 文字颜色 (sRGB, 0-255)
+    #[wasm_bindgen]
+    pub fn set_watermark_text(
+        &mut self,
+        text: &str,
+        font_data: Vec<u8>,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) -> Result<(), JsValue> {
+        let watermark =
+            Watermark::text(text, font_data, [r, g, b]).map_err(|e| JsValue::from_str(&e))?;
+        self.set_watermark_content(watermark);
+        Ok(())
+    }
+
+    /// 设置水印位置
+    ///
+    /// # 参数
+    /// * `anchor` - `"top-left"`、`"top"`、`"top-right"`、`"left"`、`"center"`、`"right"`、
+    ///   `"bottom-left"`、`"bottom"` 或 `"bottom-right"`
+    /// * `margin` - 到图像边缘的距离，相对于图像短边 (0.0 到 0.5)
+    /// * `scale` - 水印宽度占图像宽度的比例 (0.01 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_watermark_placement(
+        &mut self,
+        anchor: &str,
+        margin: f32,
+        scale: f32,
+    ) -> Result<(), JsValue> {
+        let anchor = WatermarkAnchor::from_name(anchor)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown watermark anchor: {}", anchor)))?;
+        if let Some(watermark) = self.watermark.take() {
+            self.watermark = Some(watermark.with_placement(anchor, margin, scale));
+        }
+        Ok(())
+    }
+
+    /// 设置水印不透明度 (0.0 到 1.0)
+    #[wasm_bindgen]
+    pub fn set_watermark_opacity(&mut self, opacity: f32) {
+        if let Some(watermark) = self.watermark.take() {
+            self.watermark = Some(watermark.with_opacity(opacity));
+        }
+    }
+
+    /// 设置水印是否平铺满整张图像
+    ///
+    /// # 参数
+    /// * `spacing` - 水印之间的间距，相对于水印尺寸
+    #[wasm_bindgen]
+    pub fn set_watermark_tiling(&mut self, tiled: bool, spacing: f32) {
+        if let Some(watermark) = self.watermark.take() {
+            self.watermark = Some(watermark.with_tiling(tiled, spacing));
+        }
+    }
+
+    /// 取消导出水印
+    #[wasm_bindgen]
+    pub fn clear_watermark(&mut self) {
+        self.watermark = None;
+    }
+
+    /// 以当前编辑状态创建虚拟副本并切换到新副本，返回其序号
+    ///
+    /// 虚拟副本共享同一张原图，但编辑状态、历史记录和快照相互独立。
+    #[wasm_bindgen]
+    pub fn create_virtual_copy(&mut self, name: &str) -> Result<usize, JsValue> {
+        self.renderer
+            .as_mut()
+            .map(|renderer| renderer.create_virtual_copy(name))
+            .ok_or_else(|| JsValue::from_str("Renderer not initialized"))
+    }
+
+    /// 列出所有虚拟副本的名称
+    #[wasm_bindgen]
+    pub fn list_copies(&self) -> Vec<String> {
+        self.renderer
+            .as_ref()
+            .map(|renderer| renderer.copies().iter().map(|c| c.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 当前编辑的虚拟副本序号
+    #[wasm_bindgen]
+    pub fn active_copy(&self) -> usize {
+        self.renderer
+            .as_ref()
+            .map_or(0, |renderer| renderer.active_copy())
+    }
+
+    /// 切换到指定的虚拟副本
+    #[wasm_bindgen]
+    pub fn select_copy(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.select_copy(index))
+    }
+
+    /// 删除虚拟副本 (最后一个副本不能删除)
+    #[wasm_bindgen]
+    pub fn remove_copy(&mut self, index: usize) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.remove_copy(index))
+    }
+
+    /// 重命名虚拟副本
+    #[wasm_bindgen]
+    pub fn rename_copy(&mut self, index: usize, name: &str) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.rename_copy(index, name))
+    }
+
+    /// 保存当前副本的完整编辑状态为快照，同名快照会被替换
+    #[wasm_bindgen]
+    pub fn take_snapshot(&mut self, name: &str) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Snapshot name must not be empty"));
+        }
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.take_snapshot(name);
+        }
+        Ok(())
+    }
+
+    /// 恢复当前副本的快照 (可撤销)，快照不存在时返回 false
+    #[wasm_bindgen]
+    pub fn restore_snapshot(&mut self, name: &str) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.restore_snapshot(name))
+    }
+
+    /// 删除当前副本的快照
+    #[wasm_bindgen]
+    pub fn remove_snapshot(&mut self, name: &str) -> bool {
+        self.renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.remove_snapshot(name))
+    }
+
+    /// 列出当前副本的快照名称
+    #[wasm_bindgen]
+    pub fn list_snapshots(&self) -> Vec<String> {
+        self.renderer
+            .as_ref()
+            .map(|renderer| renderer.copies()[renderer.active_copy()].snapshot_names())
+            .unwrap_or_default()
+    }
+
+    /// 渲染指定的虚拟副本，不切换当前编辑的副本
+    #[wasm_bindgen]
+    pub fn render_copy(&mut self, index: usize) -> Result<(), JsValue> {
+        let rendered = self
+            .renderer
+            .as_mut()
+            .is_some_and(|renderer| renderer.render_copy(index));
+        if rendered {
+            Ok(())
+        } else {
+            Err(JsValue::from_str(&format!("Unknown copy: {}", index)))
+        }
+    }
+
+    /// 把指定的虚拟副本导出为 JPEG 格式
+    ///
+    /// # 参数
+    /// * `index` - 虚拟副本序号
+    /// * `quality` - JPEG 质量 (0-100)
+    #[wasm_bindgen]
+    pub fn export_copy_jpeg(&self, index: usize, quality: u8) -> Result<JsValue, JsValue> {
+        let state = self
+            .renderer
+            .as_ref()
+            .and_then(|renderer| renderer.copies().get(index))
+            .map(|copy| copy.state.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown copy: {}", index)))?;
+        self.export_state(&state, quality)
+    }
+}
+
+impl WasmImageEditor {
+    fn add_mask(&mut self, mask: Mask) -> Result<usize, JsValue> {
+        let renderer = self
+            .renderer
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Renderer not initialized"))?;
+        renderer.add_mask(mask).ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "At most {} masks ({} brush masks) are supported",
+                MAX_MASKS, MAX_BRUSH_MASKS
+            ))
+        })
+    }
+
+    /// 替换水印内容，保留已有的位置、不透明度和平铺设置
+    fn set_watermark_content(&mut self, watermark: Watermark) {
+        self.watermark = Some(match self.watermark.take() {
+            Some(current) => Watermark {
+                content: watermark.content,
+                ..current
+            },
+            None => watermark,
+        });
+    }
+
+    /// 生成预览代理并上传到 GPU 纹理
+    fn upload_proxy(&mut self) -> Result<(), JsValue> {
+        let (Some(source), Some(renderer)) = (self.source.as_ref(), self.renderer.as_mut()) else {
+            return Ok(());
+        };
+        let proxy = Proxy::new(source, self.proxy_dimension);
+        renderer.load_proxy(
+            &proxy.image.data,
+            proxy.image.width,
+            proxy.image.height,
+            ImageSize::new(source.width, source.height),
+            source.color_space,
+        )
+    }
+
+    /// 最后一个满足 `stop` 的几何操作的输入图像 (预览代理分辨率)，
+    /// 没有这样的操作时为全部几何操作的结果
+    fn operation_input_proxy(&self, stop: impl Fn(&EditOperation) -> bool) -> Option<Image> {
+        let (source, renderer) = (self.source.as_ref()?, self.renderer.as_ref()?);
+        let operations = renderer.operations();
+        let preceding = operations
+            .iter()
+            .rposition(stop)
+            .unwrap_or(operations.len());
+        let proxy = Proxy::new(source, self.proxy_dimension);
+        Some(ImageTransform::apply_operations(
+            &proxy.image,
+            &operations[..preceding],
+        ))
+    }
+
+    /// 从原图重新渲染完整的编辑栈并编码为 JPEG
+    fn export_state(&self, state: &EditState, quality: u8) -> Result<JsValue, JsValue> {
+        let source = self
+            .source
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("No image loaded"))?;
+        let image = EditPipeline::render_layered(source, state, &self.layer_images);
+        let config = ExportConfig::jpeg(quality).with_watermark(self.watermark.clone());
+        let bytes = ImageEncoder::encode(&image, &config).map_err(|e| JsValue::from_str(&e))?;
+        Ok(js_sys::Uint8Array::from(bytes.as_slice()).into())
+    }
+
+    fn set_color_wheel(&mut self, range: ToneRange, hue: f32, saturation: f32, luminance: f32) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_color_wheel(range, hue, saturation, luminance);
+        }
     }
 }